@@ -1,32 +1,24 @@
-use std::{
-    cmp::{max, min},
-    collections::HashMap,
-    num::ParseIntError,
-};
+use std::collections::HashMap;
+
+use advent_of_code::parse::{self, ParseError};
 
 advent_of_code::solution!(1);
 
-pub fn part_one(input: &str) -> Option<u32> {
+pub fn part_one(input: &str) -> Result<u64, ParseError> {
     let (mut lefts, mut rights) = get_lists(input)?;
 
     lefts.sort();
     rights.sort();
 
-    let mut differences = vec![];
-    for i in 0..lefts.len() {
-        let max = max(lefts[i], rights[i]);
-        let min = min(lefts[i], rights[i]);
-
-        differences.push(max - min);
-    }
+    let differences = lefts.iter().zip(&rights).map(|(l, r)| l.abs_diff(*r));
 
-    Some(differences.iter().sum())
+    Ok(differences.sum())
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
+pub fn part_two(input: &str) -> Result<i64, ParseError> {
     let (lefts, rights) = get_lists(input)?;
 
-    let mut rights_count: HashMap<u32, u32> = HashMap::new();
+    let mut rights_count: HashMap<i64, i64> = HashMap::new();
     for right in rights {
         *rights_count.entry(right).or_default() += 1;
     }
@@ -37,41 +29,12 @@ pub fn part_two(input: &str) -> Option<u32> {
         count += left * right_count;
     }
 
-    Some(count)
+    Ok(count)
 }
 
-fn get_lists(input: &str) -> Option<(Vec<u32>, Vec<u32>)> {
-    let lines = input.lines();
-    let mut lefts = vec![];
-    let mut rights = vec![];
-    for line in lines {
-        let mut split = line.split(" ").filter(|&s| !s.is_empty());
-
-        let left = split.next()?;
-        let right = split.next()?;
-
-        let left: Result<u32, ParseIntError> = left.parse();
-        let left: u32 = match left {
-            Ok(v) => v,
-            Err(e) => {
-                println!("Couldn't get u32 from left: {e}");
-                return None;
-            }
-        };
-        let right: Result<u32, ParseIntError> = right.parse();
-        let right: u32 = match right {
-            Ok(v) => v,
-            Err(e) => {
-                println!("Couldn't get u32 from right: {e}");
-                return None;
-            }
-        };
-
-        lefts.push(left);
-        rights.push(right);
-    }
-
-    Some((lefts, rights))
+fn get_lists(input: &str) -> Result<(Vec<i64>, Vec<i64>), ParseError> {
+    let pairs = parse::fixed_per_line::<2>(input)?;
+    Ok(pairs.into_iter().map(|[left, right]| (left, right)).unzip())
 }
 
 #[cfg(test)]
@@ -81,12 +44,12 @@ mod tests {
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(11));
+        assert_eq!(result, Ok(11));
     }
 
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(31));
+        assert_eq!(result, Ok(31));
     }
 }
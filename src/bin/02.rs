@@ -1,17 +1,17 @@
-use std::num::ParseIntError;
-
+use advent_of_code::aoc_debug;
+use advent_of_code::parse::{self, ParseError};
 use colored::Colorize;
 
 advent_of_code::solution!(2);
 
-pub fn part_one(input: &str) -> Option<u32> {
+pub fn part_one(input: &str) -> Result<u32, ParseError> {
     let reports = get_reports(input)?;
-    Some(get_safe_reports_count(reports, false))?
+    Ok(get_safe_reports_count(reports, false))
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
+pub fn part_two(input: &str) -> Result<u32, ParseError> {
     let reports = get_reports(input)?;
-    Some(get_safe_reports_count(reports, true))?
+    Ok(get_safe_reports_count(reports, true))
 }
 
 #[derive(Debug)]
@@ -21,27 +21,11 @@ enum Direction {
     Decreasing,
 }
 
-fn get_reports(input: &str) -> Option<Vec<Vec<u32>>> {
-    let lines = input.lines();
-    let mut reports = vec![];
-    for line in lines {
-        let mut report = vec![];
-        for number in line.split(" ") {
-            let n: Result<u32, ParseIntError> = number.parse();
-            match n {
-                Ok(n) => report.push(n),
-                Err(_) => {
-                    println!("Couldn't parse number to u32: {}", number);
-                    return None;
-                }
-            }
-        }
-        reports.push(report);
-    }
-    Some(reports)
+fn get_reports(input: &str) -> Result<Vec<Vec<u32>>, ParseError> {
+    parse::lines(input).map(|line| line.list(" ")).collect()
 }
 
-fn get_safe_reports_count(reports: Vec<Vec<u32>>, problem_dampler: bool) -> Option<u32> {
+fn get_safe_reports_count(reports: Vec<Vec<u32>>, problem_dampler: bool) -> u32 {
     let mut count = 0;
     for report in reports {
         let mut safe = is_safe_report(report.clone());
@@ -57,13 +41,13 @@ fn get_safe_reports_count(reports: Vec<Vec<u32>>, problem_dampler: bool) -> Opti
         }
         if safe {
             count += 1;
-            println!("{:?} {}", report, "safe".green());
+            aoc_debug!("{:?} {}", report, "safe".green());
         } else {
-            println!("{:?} {}", report, "unsafe".red());
+            aoc_debug!("{:?} {}", report, "unsafe".red());
         }
     }
 
-    Some(count)
+    count
 }
 
 fn is_safe_report(report: Vec<u32>) -> bool {
@@ -126,12 +110,18 @@ mod tests {
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(2));
+        assert_eq!(result, Ok(2));
     }
 
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(4));
+        assert_eq!(result, Ok(4));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = part_one("7 6 4\n1 2 x 4").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 5));
     }
 }
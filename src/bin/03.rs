@@ -1,125 +1,80 @@
-use regex::{Captures, Regex};
+use advent_of_code::vm::{self, Flow, Kind, Machine, State, Token, VmError, Word};
 
 advent_of_code::solution!(3);
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let lines = input.lines();
-    let mut total_sum = 0;
-    for line in lines {
-        let instructions = parse_instruction(line)?;
-        let mut sum = 0;
-        for m in instructions {
-            if let Instruction::Multiplication(_, _) = m {
-                sum += m.multiply()?;
-            }
-        }
-        total_sum += sum;
-    }
+pub fn part_one(input: &str) -> Result<Word, VmError> {
+    sum_products(input, |instruction| {
+        matches!(instruction, Instruction::Multiplication(_, _))
+    })
+}
 
-    Some(total_sum)
+pub fn part_two(input: &str) -> Result<Word, VmError> {
+    sum_products(input, |_| true)
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let lines = input.lines();
+/// Registers of the machine.
+const SUM: usize = 0;
+const IS_DOING: usize = 1;
+
+/// Run the instructions of every line that pass `filter` and sum up the results.
+fn sum_products(input: &str, filter: impl Fn(&Instruction) -> bool) -> Result<Word, VmError> {
     let mut total_sum = 0;
-    for line in lines {
-        let instructions = parse_instruction(line)?;
-        let mut sum = 0;
-        let mut is_doing = true;
-        for i in instructions {
-            match i {
-                Instruction::Multiplication(_, _) => {
-                    if is_doing {
-                        sum += i.multiply()?;
-                    }
-                }
-                Instruction::Do => {
-                    is_doing = true;
-                }
-                Instruction::Dont => {
-                    is_doing = false;
-                }
-            }
-        }
-        total_sum += sum;
+    for line in input.lines() {
+        let mut program: Vec<Instruction> = vm::scan(line);
+        program.retain(&filter);
+
+        let mut machine = Machine::new(program, 2);
+        machine.registers_mut()[IS_DOING] = 1;
+        machine.run()?;
+        total_sum += machine.registers()[SUM];
     }
 
-    Some(total_sum)
+    Ok(total_sum)
 }
 
-const INSTRUCTION_MATCHER: &str =
-    r"(?<mul>mul\((?<x>\d{1,3}),(?<y>\d{1,3})\))|(?<do>do\(\))|(?<dont>don't\(\))";
-
 #[derive(Debug)]
 enum Instruction {
-    Multiplication(u32, u32),
+    Multiplication(Word, Word),
     Do,
     Dont,
 }
 
-impl Instruction {
-    fn new(capture: Captures<'_>) -> Option<Instruction> {
-        match capture.name("do") {
-            Some(_) => return Some(Instruction::Do),
-            None => {}
-        }
-
-        match capture.name("dont") {
-            Some(_) => return Some(Instruction::Dont),
-            None => {}
-        }
-
-        match capture.name("mul") {
-            Some(_) => {
-                let x = capture.name("x")?.as_str();
-                let y = capture.name("y")?.as_str();
-
-                let x = x.parse();
-                let y = y.parse();
-
-                let x = match x {
-                    Ok(v) => v,
-                    Err(e) => {
-                        println!("Couldn't parse u32 for {:#?}: {e}", capture);
-                        return None;
-                    }
-                };
-                let y = match y {
-                    Ok(v) => v,
-                    Err(e) => {
-                        println!("Couldn't parse u32 for {:#?}: {e}", capture);
-                        return None;
-                    }
-                };
-
-                return Some(Instruction::Multiplication(x, y));
+impl vm::Instruction for Instruction {
+    fn decode(tokens: &[Token<'_>]) -> Option<(Self, usize)> {
+        let (name, args, used) = vm::decode_call(tokens)?;
+        let instruction = match args.as_slice() {
+            [x, y] if name.ends_with("mul") => {
+                Instruction::Multiplication(operand(x)?, operand(y)?)
             }
-            None => return None,
-        }
+            [] if name.ends_with("don't") => Instruction::Dont,
+            [] if name.ends_with("do") => Instruction::Do,
+            _ => return None,
+        };
+        Some((instruction, used))
     }
 
-    fn multiply(&self) -> Option<u32> {
+    fn execute(&self, state: &mut State) -> Result<Flow, String> {
         match self {
-            Instruction::Multiplication(n1, n2) => Some(n1 * n2),
-            _ => None,
+            Instruction::Multiplication(x, y) => {
+                if state.registers[IS_DOING] != 0 {
+                    state.registers[SUM] += x * y;
+                }
+            }
+            Instruction::Do => state.registers[IS_DOING] = 1,
+            Instruction::Dont => state.registers[IS_DOING] = 0,
         }
+        Ok(Flow::Next)
     }
 }
 
-fn parse_instruction(input: &str) -> Option<Vec<Instruction>> {
-    let re = Regex::new(INSTRUCTION_MATCHER);
-    let re = match re {
-        Ok(v) => v,
-        Err(e) => {
-            println!("{e}");
-            return None;
-        }
-    };
-    let mut instructions = vec![];
-    for capture in re.captures_iter(input) {
-        instructions.push(Instruction::new(capture)?);
+/// Operands of `mul` have one to three digits.
+fn operand(token: &Token<'_>) -> Option<Word> {
+    let is_digits = token.text.len() <= 3 && token.text.bytes().all(|b| b.is_ascii_digit());
+    if token.kind == Kind::Int && is_digits {
+        token.int()
+    } else {
+        None
     }
-    Some(instructions)
 }
 
 #[cfg(test)]
@@ -129,12 +84,12 @@ mod tests {
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(161));
+        assert_eq!(result, Ok(161));
     }
 
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(48));
+        assert_eq!(result, Ok(48));
     }
 }
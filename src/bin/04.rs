@@ -1,155 +1,52 @@
-use std::usize;
-
-use array2d::Array2D;
+use advent_of_code::grid::{Grid, GridError, Pos, DELTAS8};
 
 advent_of_code::solution!(4);
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let grid = make_grid(input)?;
+pub fn part_one(input: &str) -> Result<usize, GridError> {
+    let grid = Grid::parse_chars(input)?;
 
-    let mut count = 0;
-    for row in 0..grid.row_len() {
-        for column in 0..grid.column_len() {
-            count += count_words(&grid, row, column);
-        }
-    }
-    Some(count)
+    let count = grid.positions().map(|pos| count_words(&grid, pos)).sum();
+    Ok(count)
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let grid = make_grid(input)?;
+pub fn part_two(input: &str) -> Result<usize, GridError> {
+    let grid = Grid::parse_chars(input)?;
 
-    let mut count = 0;
-    for row in 0..grid.row_len() {
-        for column in 0..grid.column_len() {
-            count += count_xs(&grid, row, column);
-        }
-    }
-    Some(count)
+    let count = grid.positions().filter(|pos| is_cross(&grid, *pos)).count();
+    Ok(count)
 }
 
 const WORD: &str = "XMAS";
-const CROSS_WORD: &str = "AMS";
-
-enum Direction {
-    NorthEast,
-    East,
-    SouthEast,
-    South,
-    SouthWest,
-    West,
-    NorthWest,
-    North,
-}
-
-impl Direction {
-    fn step_towards(&self, row: usize, column: usize, step: usize) -> Option<(usize, usize)> {
-        let (row, column) = match self {
-            Direction::NorthEast => (row.checked_sub(step), column.checked_add(step)),
-            Direction::East => (Some(row), column.checked_add(step)),
-            Direction::SouthEast => (row.checked_add(step), column.checked_add(step)),
-            Direction::South => (row.checked_add(step), Some(column)),
-            Direction::SouthWest => (row.checked_add(step), column.checked_sub(step)),
-            Direction::West => (Some(row), column.checked_sub(step)),
-            Direction::NorthWest => (row.checked_sub(step), column.checked_sub(step)),
-            Direction::North => (row.checked_sub(step), Some(column)),
-        };
-        Some((row?, column?))
-    }
-
-    fn all_directions() -> Vec<Direction> {
-        let mut directions = Vec::new();
-        directions.push(Direction::NorthEast);
-        directions.push(Direction::East);
-        directions.push(Direction::SouthEast);
-        directions.push(Direction::South);
-        directions.push(Direction::SouthWest);
-        directions.push(Direction::West);
-        directions.push(Direction::NorthWest);
-        directions.push(Direction::North);
-        directions
-    }
-
-    fn diagonals() -> Vec<Direction> {
-        let mut directions = Vec::new();
-        directions.push(Direction::NorthEast);
-        directions.push(Direction::SouthEast);
-        directions.push(Direction::SouthWest);
-        directions.push(Direction::NorthWest);
-        directions
-    }
+const CROSS_WORD: &str = "MAS";
+
+fn count_words(grid: &Grid<char>, pos: Pos) -> usize {
+    DELTAS8
+        .iter()
+        .filter(|delta| {
+            grid.ray(pos, **delta)
+                .map(|(_, c)| *c)
+                .take(WORD.len())
+                .eq(WORD.chars())
+        })
+        .count()
 }
 
-fn make_grid(input: &str) -> Option<Array2D<char>> {
-    let lines = input.lines();
-    let mut rows = vec![];
-    for line in lines {
-        rows.push(line.chars().collect());
+fn is_cross(grid: &Grid<char>, pos: Pos) -> bool {
+    let middle = CROSS_WORD.chars().nth(1).unwrap();
+    if grid.get(pos) != Some(&middle) {
+        return false;
     }
 
-    let grid = Array2D::from_rows(&rows);
-    let grid = match grid {
-        Ok(v) => v,
-        Err(e) => {
-            println!("Couldn't make grid from rows: {}", e);
-            return None;
-        }
-    };
-    Some(grid)
-}
-
-fn count_words(grid: &Array2D<char>, row: usize, column: usize) -> u32 {
-    if grid.get(row, column) != Some(&WORD.chars().next().unwrap_or('.')) {
-        return 0;
-    }
-
-    let mut count = 0;
-    for direction in Direction::all_directions() {
-        let mut word_found = true;
-        for step in 1..WORD.len() {
-            let coordinates = direction.step_towards(row, column, step);
-            let (r, c) = match coordinates {
-                Some((v1, v2)) => (v1, v2),
-                None => {
-                    word_found = false;
-                    break;
-                }
-            };
-            if grid.get(r, c) != Some(&WORD.chars().nth(step).unwrap_or('.')) {
-                word_found = false;
-                break;
-            }
-        }
-        if word_found {
-            count += 1;
-        }
-    }
-    count
-}
-
-fn count_xs(grid: &Array2D<char>, row: usize, column: usize) -> u32 {
-    if grid.get(row, column) != Some(&CROSS_WORD.chars().next().unwrap_or('.')) {
-        return 0;
-    }
-    let mut diagonal_chars = vec![];
-    for direction in Direction::diagonals() {
-        let coordinates = direction.step_towards(row, column, 1);
-        let (r, c) = match coordinates {
-            Some((v1, v2)) => (v1, v2),
-            None => return 0,
+    // both diagonals through the middle have to spell the word in either direction.
+    let is_word = |from: (i64, i64)| {
+        let (Some(start), Some(end)) = (grid.step(pos, from), grid.step(pos, (-from.0, -from.1)))
+        else {
+            return false;
         };
-        let char = grid.get(r, c);
-        if char != Some(&CROSS_WORD.chars().nth(1).unwrap_or('.'))
-            && char != Some(&CROSS_WORD.chars().nth(2).unwrap_or('.'))
-        {
-            return 0;
-        }
-        diagonal_chars.push(Some(char));
-    }
-    if diagonal_chars[0] == diagonal_chars[2] || diagonal_chars[1] == diagonal_chars[3] {
-        return 0;
-    }
-    1
+        let word: String = [grid[start], middle, grid[end]].iter().collect();
+        word == CROSS_WORD || word.chars().rev().eq(CROSS_WORD.chars())
+    };
+    is_word((-1, -1)) && is_word((1, -1))
 }
 
 #[cfg(test)]
@@ -159,12 +56,12 @@ mod tests {
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(18));
+        assert_eq!(result, Ok(18));
     }
 
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(9));
+        assert_eq!(result, Ok(9));
     }
 }
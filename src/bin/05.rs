@@ -1,138 +1,100 @@
-use core::panic;
-use std::{
-    cmp::Ordering,
-    collections::{HashMap, HashSet},
-};
+use std::error::Error;
+use std::fmt::{self, Display};
+
+use advent_of_code::aoc_debug;
+use advent_of_code::order::{Cycle, Order};
+use advent_of_code::parse::{self, ParseError};
 
 advent_of_code::solution!(5);
 
-pub fn part_one(input: &str) -> Option<u32> {
+pub fn part_one(input: &str) -> Result<u32, PrintingError> {
     let (rules, updates) = parse_printing_instructions(input)?;
-    let mut count = 0;
-    for update in updates {
-        count += check_update(update, &rules, false).unwrap_or(0);
-    }
-    Some(count)
+    let count = updates
+        .iter()
+        .filter(|update| rules.respects(update))
+        .map(|update| middle_page(update))
+        .sum();
+    Ok(count)
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
+pub fn part_two(input: &str) -> Result<u32, PrintingError> {
     let (rules, updates) = parse_printing_instructions(input)?;
     let mut count = 0;
     for update in updates {
-        count += check_update(update, &rules, true).unwrap_or(0);
+        if let Some((page1, page2)) = rules.violation(&update) {
+            aoc_debug!("{:?}: {page2} must be printed before {page1}", update);
+            let sorted = rules
+                .sort_subset(&update)
+                .map_err(|cycle| PrintingError::Unsortable {
+                    update: update.clone(),
+                    cycle,
+                })?;
+            aoc_debug!("sorted\t\t{:?}", sorted);
+            count += middle_page(&sorted);
+        }
     }
-    Some(count)
+    Ok(count)
 }
 
-fn parse_printing_instructions(input: &str) -> Option<(HashMap<u32, Vec<u32>>, Vec<Vec<u32>>)> {
-    let mut lines = input.lines();
-    let mut rules = HashMap::new();
-    let mut updates = vec![];
-    let mut parsing_rules = true;
-    loop {
-        let line = lines.next();
-        let line = match line {
-            Some(v) => v,
-            None => break,
-        };
-        if line == "" {
-            parsing_rules = false;
-            continue;
-        }
-        if parsing_rules {
-            let mut line = line.split("|");
-            let page1 = line.next()?.parse();
-            let page1: u32 = match page1 {
-                Ok(v) => v,
-                Err(_) => return None,
-            };
-            let page2 = line.next()?.parse();
-            let page2 = match page2 {
-                Ok(v) => v,
-                Err(_) => return None,
-            };
-            let rule = rules.entry(page2).or_insert(vec![]);
-            rule.push(page1);
-        } else {
-            let line = line.split(",");
-            let mut pages = vec![];
-            for page in line {
-                let page = page.parse();
-                let page = match page {
-                    Ok(v) => v,
-                    Err(_) => return None,
-                };
-                pages.push(page);
-            }
-            updates.push(pages);
+#[derive(Debug, PartialEq)]
+pub enum PrintingError {
+    Parse(ParseError),
+    /// The rules for the pages of `update` contain a cycle.
+    Unsortable {
+        update: Vec<u32>,
+        cycle: Cycle<u32>,
+    },
+}
+
+impl Display for PrintingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrintingError::Parse(e) => write!(f, "{e}"),
+            PrintingError::Unsortable { update, .. } => write!(f, "couldn't sort {update:?}"),
         }
     }
-    Some((rules, updates))
 }
 
-fn check_update(update: Vec<u32>, rules: &HashMap<u32, Vec<u32>>, sort: bool) -> Option<u32> {
-    println!("checking\t{:?}", update);
-    let mut relevant_rules = HashSet::new();
-    for page in &update {
-        if let Some(_) = relevant_rules.get(&page) {
-            if sort {
-                return sort_update(update, rules);
-            } else {
-                return None;
-            }
-        }
-        let rules = match rules.get(&page) {
-            Some(v) => v,
-            None => continue,
-        };
-        for rule in rules {
-            relevant_rules.insert(rule);
+impl Error for PrintingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PrintingError::Parse(_) => None,
+            PrintingError::Unsortable { cycle, .. } => Some(cycle),
         }
     }
-    match sort {
-        true => None,
-        false => Some(update[update.len() / 2]),
+}
+
+impl From<ParseError> for PrintingError {
+    fn from(e: ParseError) -> Self {
+        PrintingError::Parse(e)
     }
 }
 
-fn compare_page(page1: u32, page2: u32, rules: &HashMap<u32, Vec<u32>>) -> Ordering {
-    let rule = match rules.get(&page2) {
-        Some(v) => v,
-        None => return Ordering::Equal,
+fn parse_printing_instructions(input: &str) -> Result<(Order<u32>, Vec<Vec<u32>>), ParseError> {
+    let blocks = parse::blocks(input);
+    let [rules_block, updates_block] = blocks[..] else {
+        return Err(ParseError {
+            line: 1,
+            column: 1,
+            message: format!("expected rules and updates, found {} blocks", blocks.len()),
+        });
     };
-    for page in rule {
-        if *page == page1 {
-            return Ordering::Less;
-        }
-    }
-    Ordering::Equal
+
+    let rules = rules_block
+        .lines()
+        .map(|line| line.pair("|"))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let updates = updates_block
+        .lines()
+        .map(|line| line.list(","))
+        .collect::<Result<_, _>>()?;
+
+    Ok((Order::from_rules(rules), updates))
 }
 
-fn sort_update(mut update: Vec<u32>, rules: &HashMap<u32, Vec<u32>>) -> Option<u32> {
-    loop {
-        let mut swapped = false;
-        for i in 1..update.len() {
-            match compare_page(update[i - 1], update[i], rules) {
-                Ordering::Greater => panic!(
-                    "Greater was found comparing {} and {}",
-                    update[i - 1],
-                    update[i]
-                ),
-                Ordering::Equal => continue,
-                Ordering::Less => {
-                    let swap = update[i];
-                    update[i] = update[i - 1];
-                    update[i - 1] = swap;
-                    swapped = true;
-                }
-            }
-        }
-        if !swapped {
-            break;
-        }
-        println!("sorted\t\t{:?}", update);
-    }
-    Some(update[update.len() / 2])
+fn middle_page(update: &[u32]) -> u32 {
+    update[update.len() / 2]
 }
 
 #[cfg(test)]
@@ -142,12 +104,24 @@ mod tests {
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(143));
+        assert_eq!(result, Ok(143));
     }
 
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(123));
+        assert_eq!(result, Ok(123));
+    }
+
+    #[test]
+    fn test_cyclic_rules() {
+        let result = part_two("1|2\n2|1\n\n1,2,3\n");
+        assert_eq!(
+            result,
+            Err(PrintingError::Unsortable {
+                update: vec![1, 2, 3],
+                cycle: Cycle(vec![2, 1, 2])
+            })
+        );
     }
 }
@@ -1,225 +1,215 @@
-use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::{self, Display};
 
-use array2d::Array2D;
+use advent_of_code::geometry::Direction;
+use advent_of_code::grid::{Grid, GridError, Pos, VisitedSet};
+use advent_of_code::par;
 
 advent_of_code::solution!(6);
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let mut map = Map::new(input)?;
-    let mut done = false;
-    while !done {
-        done = map.step()?;
-    }
-    Some(map.cells_visited_count)
+pub fn part_one(input: &str) -> Result<usize, MapError> {
+    let map = Map::parse(input)?;
+    let mut visited = VisitedSet::for_grid(&map.walls, 1);
+    let count = map
+        .patrol()?
+        .iter()
+        .filter(|guard| visited.insert(guard.pos))
+        .count();
+    Ok(count)
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let mut map = Map::new(input)?;
-    let mut done = false;
-    while !done {
-        done = map.step()?;
-        let mut map_tampered = map.clone();
-        map_tampered.check_loop()?;
-        map.obstacles = map_tampered.obstacles;
-    }
+pub fn part_two(input: &str) -> Result<usize, MapError> {
+    let map = Map::parse(input)?;
+    let path = map.patrol()?;
+    let jumps = Jumps::new(&map.walls);
 
-    let obstacles: u32 = u32::try_from(map.obstacles.len()).unwrap_or(0);
-    Some(obstacles)
+    // an obstacle has to be in place before the guard first walks onto its cell, so it is checked from the
+    // guard's state just before that.
+    let mut visited = VisitedSet::for_grid(&map.walls, 1);
+    visited.insert(map.start.pos);
+    let candidates: Vec<Guard> = path
+        .windows(2)
+        .filter(|states| states[0].pos != states[1].pos && visited.insert(states[1].pos))
+        .map(|states| states[0])
+        .collect();
+
+    // every candidate is checked in parallel, with one set of visited states per thread.
+    let loops = par::par_map_init(
+        &candidates,
+        || VisitedSet::for_grid(&map.walls, 4),
+        |states, guard| jumps.is_loop(states, guard),
+    )
+    .into_iter()
+    .filter(|is_loop| *is_loop)
+    .count();
+    Ok(loops)
 }
 
 const TILE: char = '.';
 const WALL: char = '#';
-const GUARD_EAST: char = '>';
-const GUARD_SOUTH: char = 'v';
-const GUARD_WEST: char = '<';
-const GUARD_NORTH: char = '^';
-
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-enum Direction {
-    East,
-    South,
-    West,
-    North,
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Guard {
+    pos: Pos,
+    d: Direction,
 }
 
-impl Direction {
-    fn rotate_right(&self) -> Direction {
+#[derive(Debug, PartialEq)]
+pub enum MapError {
+    Grid(GridError),
+    NoGuard,
+    /// The guard never leaves the map, walking in a loop from this position.
+    Loop(Pos),
+}
+
+impl Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Direction::East => Direction::South,
-            Direction::South => Direction::West,
-            Direction::West => Direction::North,
-            Direction::North => Direction::East,
+            MapError::Grid(_) => write!(f, "couldn't parse map"),
+            MapError::NoGuard => write!(f, "couldn't find the guard"),
+            MapError::Loop(pos) => write!(f, "the guard walks in a loop from {pos:?}"),
         }
     }
-
-    fn step(&self, row: usize, column: usize) -> Option<(usize, usize)> {
-        let (row, column) = match self {
-            Direction::East => (Some(row), column.checked_add(1)),
-            Direction::South => (row.checked_add(1), Some(column)),
-            Direction::West => (Some(row), column.checked_sub(1)),
-            Direction::North => (row.checked_sub(1), Some(column)),
-        };
-        Some((row?, column?))
-    }
-}
-
-#[derive(Clone, Debug)]
-enum Cell {
-    Guard(Direction),
-    Tile(bool),
-    Wall,
 }
 
-impl Cell {
-    fn new(input: char) -> Option<Cell> {
-        match input {
-            TILE => Some(Cell::Tile(false)),
-            WALL => Some(Cell::Wall),
-            GUARD_EAST => Some(Cell::Guard(Direction::East)),
-            GUARD_SOUTH => Some(Cell::Guard(Direction::South)),
-            GUARD_WEST => Some(Cell::Guard(Direction::West)),
-            GUARD_NORTH => Some(Cell::Guard(Direction::North)),
-            _ => None,
+impl Error for MapError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MapError::Grid(e) => Some(e),
+            MapError::NoGuard | MapError::Loop(_) => None,
         }
     }
 }
 
-type Guard = (usize, usize, Direction);
-
-#[derive(Debug, Clone)]
 struct Map {
-    grid: Array2D<Cell>,
-    guard: Guard,
-    cells_visited_count: u32,
-    obstacles: HashSet<(usize, usize)>,
+    /// `true` for walls.
+    walls: Grid<bool>,
+    start: Guard,
 }
 
 impl Map {
-    fn new(input: &str) -> Option<Map> {
-        let lines = input.lines();
-        let mut rows = vec![];
-        let mut x = 0;
-        let mut guard = (0, 0, Direction::East);
-        for line in lines {
-            let mut y = 0;
-            let mut row = vec![];
-            for c in line.chars() {
-                let cell = match Cell::new(c)? {
-                    Cell::Guard(d) => {
-                        guard = (x, y, d);
-                        Cell::Tile(false)
-                    }
-                    v => v,
-                };
-                row.push(cell);
-                y += 1;
-            }
-            rows.push(row);
-            x += 1;
+    /// The guard is one of `^>v<`, which also gives its facing.
+    fn parse(input: &str) -> Result<Map, MapError> {
+        let is_cell = |c: char| c == TILE || c == WALL || Direction::from_char(c).is_some();
+        let grid = Grid::parse(input, |c| is_cell(c).then_some(c)).map_err(MapError::Grid)?;
+        let pos = grid
+            .position(|c| Direction::from_char(*c).is_some())
+            .ok_or(MapError::NoGuard)?;
+        let d = Direction::from_char(grid[pos]).ok_or(MapError::NoGuard)?;
+        Ok(Map {
+            walls: grid.map(|c| *c == WALL),
+            start: Guard { pos, d },
+        })
+    }
+
+    /// The guard's state after its next move, or `None` if it leaves the grid.
+    fn next_guard(&self, guard: Guard) -> Option<Guard> {
+        let next = self.walls.step(guard.pos, guard.d.delta())?;
+        if self.walls[next] {
+            Some(Guard {
+                d: guard.d.rotate_right(),
+                ..guard
+            })
+        } else {
+            Some(Guard { pos: next, ..guard })
         }
+    }
 
-        let grid = match Array2D::from_rows(&rows) {
-            Ok(v) => v,
-            Err(e) => {
-                println!("Couldn't make grid from rows: {}", e);
-                return None;
+    /// Every state of the guard from the start until it leaves the grid.
+    fn patrol(&self) -> Result<Vec<Guard>, MapError> {
+        let mut states = VisitedSet::for_grid(&self.walls, 4);
+        let mut path = vec![];
+        let mut guard = Some(self.start);
+        while let Some(current) = guard {
+            if !states.insert_layer(current.pos, current.d as usize) {
+                return Err(MapError::Loop(current.pos));
             }
-        };
-        Some(Map {
-            grid,
-            guard,
-            cells_visited_count: 0,
-            obstacles: HashSet::new(),
-        })
+            path.push(current);
+            guard = self.next_guard(current);
+        }
+        Ok(path)
     }
+}
 
-    /// Returns whether the step was out of the grid
-    fn step(&mut self) -> Option<bool> {
-        // have the guard visit the current tile
-        let current_cell = match self.grid.get(self.guard.0, self.guard.1) {
-            Some(v) => v,
-            None => return Some(true),
-        };
-        match current_cell {
-            Cell::Tile(visited) => {
-                if !visited {
-                    self.cells_visited_count += 1;
-                    match self.grid.set(self.guard.0, self.guard.1, Cell::Tile(true)) {
-                        Ok(_) => {}
-                        Err(_) => {
-                            println!("can't set tile");
-                            return None;
-                        }
-                    }
-                }
-            }
-            _ => {
-                println!("visiting cell is not tile");
-                return None;
-            }
-        };
+/// For every cell and direction, the cell where a guard walking straight ahead stops in front of a wall, or
+/// `None` if it walks off the grid.
+struct Jumps {
+    width: usize,
+    height: usize,
+    stops: Vec<Option<Pos>>,
+}
 
-        // have the guard step once
-        let (next_x, next_y) = match self.guard.2.step(self.guard.0, self.guard.1) {
-            Some(v) => v,
-            None => return Some(true),
-        };
-        let next_cell = match self.grid.get(next_x, next_y) {
-            Some(v) => v,
-            None => return Some(true),
+impl Jumps {
+    fn new(walls: &Grid<bool>) -> Jumps {
+        let (width, height) = (walls.width(), walls.height());
+        let mut jumps = Jumps {
+            width,
+            height,
+            stops: vec![None; 4 * width * height],
         };
-        match next_cell {
-            Cell::Tile(_) => {
-                self.guard.0 = next_x;
-                self.guard.1 = next_y;
-            }
-            Cell::Wall => self.guard.2 = self.guard.2.rotate_right(),
-            _ => {
-                println!("next cell was not a tile or a wall");
-                return None;
+        let positions: Vec<Pos> = walls.positions().collect();
+        for d in Direction::ALL {
+            // the cell in front has to be done first: it comes later in row-major order going east or south.
+            let ordered: Box<dyn Iterator<Item = &Pos>> = match d {
+                Direction::East | Direction::South => Box::new(positions.iter().rev()),
+                Direction::West | Direction::North => Box::new(positions.iter()),
+            };
+            for &pos in ordered {
+                let stop = match walls.step(pos, d.delta()) {
+                    Some(next) if walls[next] => Some(pos),
+                    Some(next) => jumps.stops[jumps.index(next, d)],
+                    None => None,
+                };
+                let index = jumps.index(pos, d);
+                jumps.stops[index] = stop;
             }
         }
-        Some(false)
+        jumps
     }
 
-    fn check_loop(&mut self) -> Option<()> {
-        // place the obstacle
-        let (next_x, next_y) = match self.guard.2.step(self.guard.0, self.guard.1) {
-            Some(v) => v,
-            None => return Some(()),
-        };
-        let next_cell = match self.grid.get(next_x, next_y) {
-            Some(v) => v,
-            None => return Some(()),
-        };
-        match next_cell {
-            Cell::Wall => return Some(()),
-            Cell::Tile(_) => match self.grid.set(next_x, next_y, Cell::Wall) {
-                Ok(_) => {}
-                Err(_) => {
-                    println!("can't set tile");
-                    return None;
-                }
-            },
+    fn index(&self, (x, y): Pos, d: Direction) -> usize {
+        (d as usize * self.height + y) * self.width + x
+    }
 
-            _ => {
-                println!("next cell was not a tile or a wall");
-                return None;
-            }
+    /// Where a guard at `pos` facing `d` stops, with `obstacle` as an extra wall.
+    fn stop(&self, (x, y): Pos, d: Direction, obstacle: Pos) -> Option<Pos> {
+        let stop = self.stops[self.index((x, y), d)];
+        let ahead = match d {
+            Direction::East => obstacle.1 == y && obstacle.0 > x,
+            Direction::South => obstacle.0 == x && obstacle.1 > y,
+            Direction::West => obstacle.1 == y && obstacle.0 < x,
+            Direction::North => obstacle.0 == x && obstacle.1 < y,
+        };
+        if !ahead {
+            return stop;
+        }
+        let distance = |(a, b): Pos| a.abs_diff(x) + b.abs_diff(y);
+        match stop {
+            Some(stop) if distance(stop) < distance(obstacle) => Some(stop),
+            _ => d.opposite().step(obstacle),
         }
+    }
 
-        // check if we find a loop
-        let mut past_guard_states: HashSet<Guard> = HashSet::new();
-        let mut done = false;
-        while !done {
-            if let Some(_) = past_guard_states.get(&self.guard) {
-                self.obstacles.insert((next_x, next_y));
-                return Some(());
+    /// Whether `guard` walks in a loop once an obstacle is put in front of it. The guard goes from wall to wall,
+    /// and its states in front of walls are kept in `states`, which is reused between checks.
+    fn is_loop(&self, states: &mut VisitedSet, guard: &Guard) -> bool {
+        let Some(obstacle) = guard.d.step(guard.pos) else {
+            return false;
+        };
+        states.clear();
+        let Guard { mut pos, mut d } = *guard;
+        loop {
+            if !states.insert_layer(pos, d as usize) {
+                return true;
+            }
+            match self.stop(pos, d, obstacle) {
+                Some(stop) => {
+                    pos = stop;
+                    d = d.rotate_right();
+                }
+                None => return false,
             }
-            past_guard_states.insert(self.guard.clone());
-            done = self.step()?;
         }
-        Some(())
     }
 }
 
@@ -230,12 +220,44 @@ mod tests {
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(41));
+        assert_eq!(result, Ok(41));
     }
 
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(6));
+        assert_eq!(result, Ok(6));
+    }
+
+    /// The grid turned a quarter clockwise, guard included.
+    fn rotate(input: &str) -> String {
+        let rows: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
+        let turn = |c: char| Direction::from_char(c).map_or(c, |d| d.rotate_right().to_char());
+        (0..rows[0].len())
+            .map(|column| {
+                let line: String = rows.iter().rev().map(|row| turn(row[column])).collect();
+                line + "\n"
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rotated() {
+        let mut input = advent_of_code::template::read_file("examples", DAY);
+        for _ in 0..3 {
+            input = rotate(&input);
+            assert_eq!(part_one(&input), Ok(41), "{input}");
+            assert_eq!(part_two(&input), Ok(6), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            part_one(".#..\n...#\n#...\n.^#.\n"),
+            Err(MapError::Loop((1, 2)))
+        );
+        assert_eq!(part_one("..#\n...\n"), Err(MapError::NoGuard));
+        assert!(matches!(part_two("..#\n.x^\n"), Err(MapError::Grid(_))));
     }
 }
@@ -1,27 +1,28 @@
+use advent_of_code::par;
+use advent_of_code::parse::{self, ParseError};
+
 advent_of_code::solution!(7);
 
-pub fn part_one(input: &str) -> Option<u64> {
-    let lines = input.lines();
-    let mut count = 0;
-    for line in lines {
-        let equation = Equation::new(line)?;
-        if equation.generate_operations_recursive(false) {
-            count += equation.result;
-        };
-    }
-    Some(count)
+pub fn part_one(input: &str) -> Result<u64, ParseError> {
+    calibration_result(input, false)
 }
 
-pub fn part_two(input: &str) -> Option<u64> {
-    let lines = input.lines();
-    let mut count = 0;
-    for line in lines {
-        let equation = Equation::new(line)?;
-        if equation.generate_operations_recursive(true) {
-            count += equation.result;
-        };
-    }
-    Some(count)
+pub fn part_two(input: &str) -> Result<u64, ParseError> {
+    calibration_result(input, true)
+}
+
+/// Sum of the results of all equations that can be made true. The equations are checked in parallel.
+fn calibration_result(input: &str, include_concat: bool) -> Result<u64, ParseError> {
+    let equations = parse::lines(input)
+        .map(Equation::new)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(par::par_sum(&equations, |equation| {
+        if equation.generate_operations_recursive(include_concat) {
+            equation.result
+        } else {
+            0
+        }
+    }))
 }
 
 #[derive(Debug, Clone)]
@@ -32,54 +33,15 @@ enum Operation {
 }
 
 impl Operation {
-    fn next(operations: &mut Vec<Operation>, include_concat: bool) -> Option<()> {
-        let mut i = 0;
-        while i < operations.len() {
-            match operations[i] {
-                Operation::Add => {
-                    operations[i] = Operation::Multiply;
-                    return Some(());
-                }
-                Operation::Multiply => {
-                    if include_concat {
-                        operations[i] = Operation::Concatenate;
-                        return Some(());
-                    } else {
-                        operations[i] = Operation::Add;
-                        i += 1;
-                    }
-                }
-                Self::Concatenate => {
-                    operations[i] = Operation::Add;
-                    i += 1;
-                }
-            }
-        }
-        None
-    }
-
+    /// `None` if the result doesn't fit into a `u64`.
     fn calculate(&self, n1: u64, n2: u64) -> Option<u64> {
-        let ten: u64 = 10;
         match self {
             Operation::Add => Some(n1 + n2),
             Operation::Multiply => Some(n1 * n2),
-            Operation::Concatenate => Some(
-                n1 * match ten.checked_pow(
-                    match n2.checked_ilog10() {
-                        Some(v) => v,
-                        None => {
-                            println!("log10 broke for {n2}");
-                            return None;
-                        }
-                    } + 1,
-                ) {
-                    Some(v) => v,
-                    None => {
-                        println!("pow10 broke for {n2}");
-                        return None;
-                    }
-                } + n2,
-            ),
+            Operation::Concatenate => {
+                let digits = n2.checked_ilog10().unwrap_or(0) + 1;
+                n1.checked_mul(10u64.checked_pow(digits)?)?.checked_add(n2)
+            }
         }
     }
 }
@@ -88,72 +50,18 @@ impl Operation {
 struct Equation {
     result: u64,
     numbers: Vec<u64>,
-    operations: Vec<Operation>,
 }
 
 impl Equation {
-    fn new(input: &str) -> Option<Equation> {
-        let mut parts = input.split(":");
-        let result = parts.next()?.parse().unwrap();
-        let numbers = parts
-            .next()?
-            .trim()
-            .split(" ")
-            .map(|v| v.parse::<u64>().unwrap())
-            .collect();
-        Some(Equation {
-            result,
-            numbers,
-            operations: vec![],
-        })
-    }
-
-    fn generate_operations(&mut self, include_concat: bool) -> bool {
-        let mut operations = vec![];
-        for _ in 1..self.numbers.len() {
-            operations.push(Operation::Add);
-        }
-        // println!("\tdefault operations: {:?}", operations);
-        self.operations = operations;
-        loop {
-            if self.check() {
-                // println!("\tequation is good");
-                return true;
-            }
-            if let None = Operation::next(&mut self.operations, include_concat) {
-                // println!("\tno more operation permutations: {:?}", self.operations);
-                return false;
-            }
-            // println!("\t{:?}", self);
-        }
-    }
-
-    fn check(&self) -> bool {
-        let mut numbers = self.numbers.iter();
-        let mut operations = self.operations.iter();
-        let mut result = *match numbers.next() {
-            Some(v) => v,
-            None => return false,
-        };
-        loop {
-            let operation = match operations.next() {
-                Some(v) => v,
-                None => break,
-            };
-            let number = *match numbers.next() {
-                Some(v) => v,
-                None => break,
-            };
-            result = operation.calculate(result, number).unwrap_or(0);
+    fn new(line: parse::Line) -> Result<Equation, ParseError> {
+        let (result, numbers): (u64, Vec<u64>) = line.key_values(":")?;
+        if numbers.len() < 2 {
+            return Err(line.error(1, "expected at least two numbers"));
         }
-        self.result == result
+        Ok(Equation { result, numbers })
     }
 
     fn generate_operations_recursive(&self, include_concat: bool) -> bool {
-        if self.numbers.len() <= 1 {
-            println!("not enough numbers");
-            return false;
-        }
         let operations = match include_concat {
             false => &[Operation::Add, Operation::Multiply].to_vec(),
             true => &[Operation::Add, Operation::Multiply, Operation::Concatenate].to_vec(),
@@ -192,12 +100,20 @@ mod tests {
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(3749));
+        assert_eq!(result, Ok(3749));
     }
 
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(11387));
+        assert_eq!(result, Ok(11387));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = part_one("190: 10 19\n3267: 81 x 27").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 10));
+        let error = part_two("83: 17").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 1));
     }
 }
@@ -1,37 +1,38 @@
-use colored::{self, Colorize};
 use std::collections::{HashMap, HashSet};
 
-use array2d::Array2D;
+use advent_of_code::viz::{self, Cell, Color, Frame};
 
 advent_of_code::solution!(8);
 
 pub fn part_one(input: &str) -> Option<usize> {
     let (antennas_by_type, grid_size) = parse_antennas(input);
     let mut antinodes = vec![];
-    for antennas in antennas_by_type {
-        let mut antinodes_found = find_antinodes(antennas.1, false, grid_size);
+    for antennas in antennas_by_type.values() {
+        let mut antinodes_found = find_antinodes(antennas.clone(), false, grid_size);
         antinodes.append(&mut antinodes_found);
     }
     let antinodes = clean_results(antinodes, grid_size);
-    // show(antennas_by_type, antinodes.clone(), grid_size);
+    viz::push_with(|| frame(&antennas_by_type, &antinodes, grid_size));
     Some(antinodes.len())
 }
 
 pub fn part_two(input: &str) -> Option<usize> {
     let (antennas_by_type, grid_size) = parse_antennas(input);
     let mut antinodes = vec![];
-    for antennas in antennas_by_type {
-        let mut antinodes_found = find_antinodes(antennas.1, true, grid_size);
+    for antennas in antennas_by_type.values() {
+        let mut antinodes_found = find_antinodes(antennas.clone(), true, grid_size);
         antinodes.append(&mut antinodes_found);
     }
     let antinodes = clean_results(antinodes, grid_size);
-    // show(antennas_by_type, antinodes.clone(), grid_size);
+    viz::push_with(|| frame(&antennas_by_type, &antinodes, grid_size));
     Some(antinodes.len())
 }
 
 const EMPTY_CHARACTER: char = '.';
 
-fn parse_antennas(input: &str) -> (HashMap<char, Vec<(i32, i32)>>, (i32, i32)) {
+type Antennas = HashMap<char, Vec<(i32, i32)>>;
+
+fn parse_antennas(input: &str) -> (Antennas, (i32, i32)) {
     let mut antennas_by_type = HashMap::new();
     let mut x = 0;
     let mut y = 0;
@@ -124,60 +125,33 @@ fn clean_results(cells: Vec<(i32, i32)>, grid_size: (i32, i32)) -> HashSet<(i32,
     cells_cleaned
 }
 
-fn show(
-    antennas_by_character: HashMap<char, Vec<(i32, i32)>>,
-    antinodes: HashSet<(i32, i32)>,
+fn frame(
+    antennas_by_character: &Antennas,
+    antinodes: &HashSet<(i32, i32)>,
     grid_size: (i32, i32),
-) {
-    println!("grid_size: {:?}", grid_size);
-    let mut grid = Array2D::filled_with(
-        ".".white(),
-        grid_size.0.try_into().unwrap(),
+) -> Frame {
+    let mut frame = Frame::new(
         grid_size.1.try_into().unwrap(),
+        grid_size.0.try_into().unwrap(),
     );
-    for (character, antennas) in antennas_by_character {
-        for antenna in antennas {
-            if let Err(_) = grid.set(
-                antenna.0.try_into().unwrap(),
-                antenna.1.try_into().unwrap(),
-                character.to_string().blue(),
-            ) {
-                println!("Couldn't set antenna{:?}", antenna);
-            }
-        }
-    }
     for antinode in antinodes {
-        let character = match grid.get(
-            antinode.0.try_into().unwrap(),
-            antinode.1.try_into().unwrap(),
-        ) {
-            Some(v) => v,
-            None => &".".red(),
-        };
-        if character == &".".white() {
-            if let Err(_) = grid.set(
-                antinode.0.try_into().unwrap(),
-                antinode.1.try_into().unwrap(),
-                "#".green(),
-            ) {
-                println!("Couldn't set antinode{:?}", antinode);
-            }
-        } else {
-            if let Err(_) = grid.set(
-                antinode.0.try_into().unwrap(),
-                antinode.1.try_into().unwrap(),
-                character.clone().green(),
-            ) {
-                println!("Couldn't set antinode{:?}", antinode);
-            }
-        }
+        frame.set_signed(
+            antinode.1.into(),
+            antinode.0.into(),
+            Cell::new('#').fg(Color::GREEN),
+        );
     }
-    for row in grid.rows_iter() {
-        for element in row {
-            print!("{element}");
+    for (character, antennas) in antennas_by_character {
+        for antenna in antennas {
+            let cell = Cell::new(*character);
+            let cell = match antinodes.contains(antenna) {
+                true => cell.fg(Color::GREEN),
+                false => cell.fg(Color::BLUE),
+            };
+            frame.set_signed(antenna.1.into(), antenna.0.into(), cell);
         }
-        println!();
     }
+    frame
 }
 
 #[cfg(test)]
@@ -1,224 +1,381 @@
-use std::usize;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use advent_of_code::parse::ParseError;
 
 advent_of_code::solution!(9);
 
-pub fn part_one(input: &str) -> Option<usize> {
-    let mut blocks = parse_blocks(input)?;
-    // print_blocks(blocks.clone());
-    fragment_blocks(&mut blocks)?;
-    // print_blocks(blocks.clone());
-    Some(checksum_blocks(blocks))
+pub fn part_one(input: &str) -> Result<usize, ParseError> {
+    Ok(parse_disk(input)?.compact_blocks())
 }
 
-pub fn part_two(input: &str) -> Option<usize> {
-    let mut files = parse_files(input)?;
-    // print_files(files.clone());
-    fragment_files(&mut files)?;
-    // print_files(files.clone());
-    Some(checksum_files(files))
+pub fn part_two(input: &str) -> Result<usize, ParseError> {
+    Ok(parse_disk(input)?.compact_files())
 }
 
-fn parse_blocks(input: &str) -> Option<Vec<Option<usize>>> {
-    let mut is_file = true;
-    let mut file_index = 0;
-    let mut blocks = vec![];
-    for c in input.chars() {
-        if c == '\n' {
-            break;
-        }
-        let Some(size) = c.to_digit(10) else {
-            println!("character {c} doesn't parse to digit");
-            return None;
-        };
-        let size = match size.try_into() {
-            Ok(v) => v,
-            Err(e) => {
-                println!("character {c} doesn't try into usize: {e}");
-                return None;
-            }
-        };
-        match is_file {
-            true => {
-                blocks.append(&mut vec![Some(file_index); size]);
-                is_file = false;
-                file_index += 1;
-            }
-            false => {
-                blocks.append(&mut vec![None; size]);
-                is_file = true;
+/// Consecutive blocks on the disk.
+#[derive(Clone, Copy, Debug)]
+struct Span {
+    start: usize,
+    len: usize,
+}
+
+impl Span {
+    /// Checksum of `id` stored in every block of the span.
+    fn checksum(&self, id: usize) -> usize {
+        id * (self.start * self.len + self.len * self.len.saturating_sub(1) / 2)
+    }
+}
+
+/// The disk map: the span of every file, indexed by file ID, and the free spans between them, in order.
+struct Disk {
+    files: Vec<Span>,
+    gaps: Vec<Span>,
+}
+
+impl Disk {
+    /// Move single blocks from the end of the disk into the leftmost free blocks, without building the blocks.
+    fn compact_blocks(&self) -> usize {
+        let mut files = self.files.clone();
+        let mut checksum = 0;
+        let mut id = files.len();
+        'gaps: for gap in &self.gaps {
+            let mut gap = *gap;
+            while gap.len > 0 {
+                // the last file that still has blocks at its end.
+                while files[id - 1].len == 0 {
+                    id -= 1;
+                    if id == 0 {
+                        break 'gaps;
+                    }
+                }
+                let file = &mut files[id - 1];
+                if file.start < gap.start {
+                    break 'gaps;
+                }
+                let len = gap.len.min(file.len);
+                checksum += Span { len, ..gap }.checksum(id - 1);
+                gap.start += len;
+                gap.len -= len;
+                file.len -= len;
             }
         }
+        checksum
+            + files
+                .iter()
+                .enumerate()
+                .map(|(id, file)| file.checksum(id))
+                .sum::<usize>()
     }
-    Some(blocks)
-}
 
-fn parse_files(input: &str) -> Option<Vec<(Option<usize>, usize)>> {
-    let mut is_file = true;
-    let mut file_index = 0;
-    let mut files = vec![];
-    for c in input.chars() {
-        if c == '\n' {
-            break;
+    /// Move whole files, from the highest ID down, into the leftmost gap they fit in.
+    ///
+    /// Gaps are kept in one min-heap of start positions per size, so a file only looks at the first gap of each
+    /// size it fits in. What is left of a gap goes into the heap of the smaller size. The spaces that files leave
+    /// behind never matter, as all files that are still to be moved are to the left of them.
+    fn compact_files(&self) -> usize {
+        let mut gaps: [BinaryHeap<Reverse<usize>>; 10] = Default::default();
+        for gap in &self.gaps {
+            gaps[gap.len].push(Reverse(gap.start));
         }
-        let Some(size) = c.to_digit(10) else {
-            println!("character {c} doesn't parse to digit");
-            return None;
-        };
-        let size = match size.try_into() {
-            Ok(v) => v,
-            Err(e) => {
-                println!("character {c} doesn't try into usize: {e}");
-                return None;
-            }
-        };
-        match is_file {
-            true => {
-                files.push((Some(file_index), size));
-                is_file = false;
-                file_index += 1;
-            }
-            false => {
-                files.push((None, size));
-                is_file = true;
+
+        let mut checksum = 0;
+        for (id, file) in self.files.iter().enumerate().rev() {
+            let mut file = *file;
+            let leftmost = (file.len.max(1)..gaps.len())
+                .filter_map(|size| gaps[size].peek().map(|Reverse(start)| (*start, size)))
+                .filter(|(start, _)| *start < file.start)
+                .min();
+            if let Some((start, size)) = leftmost {
+                gaps[size].pop();
+                gaps[size - file.len].push(Reverse(start + file.len));
+                file.start = start;
             }
+            checksum += file.checksum(id);
         }
+        checksum
     }
-    Some(files)
 }
 
-fn print_blocks(blocks: Vec<Option<usize>>) {
-    for block in blocks {
-        match block {
-            Some(v) => print!("{v}"),
-            None => print!("."),
+fn parse_disk(input: &str) -> Result<Disk, ParseError> {
+    let line = input.lines().next().unwrap_or_default();
+    let mut disk = Disk {
+        files: vec![],
+        gaps: vec![],
+    };
+    let mut start = 0;
+    for (i, c) in line.chars().enumerate() {
+        let len = c.to_digit(10).ok_or_else(|| ParseError {
+            line: 1,
+            column: i + 1,
+            message: format!("character {c} doesn't parse to digit"),
+        })? as usize;
+        let span = Span { start, len };
+        if i % 2 == 0 {
+            disk.files.push(span);
+        } else {
+            disk.gaps.push(span);
         }
+        start += len;
     }
-    println!();
+    Ok(disk)
 }
 
-fn print_files(files: Vec<(Option<usize>, usize)>) {
-    for (index, size) in files {
-        match index {
-            Some(v) => print!("{}", vec![v.to_string(); size].join("")),
-            None => print!("{}", vec!['.'.to_string(); size].join("")),
+/// The original block-by-block solution, kept to cross-check the span-based one.
+#[cfg(test)]
+mod reference {
+    pub(super) fn parse_blocks(input: &str) -> Option<Vec<Option<usize>>> {
+        let mut is_file = true;
+        let mut file_index = 0;
+        let mut blocks = vec![];
+        for c in input.chars() {
+            if c == '\n' {
+                break;
+            }
+            let size = c.to_digit(10)?.try_into().ok()?;
+            match is_file {
+                true => {
+                    blocks.append(&mut vec![Some(file_index); size]);
+                    is_file = false;
+                    file_index += 1;
+                }
+                false => {
+                    blocks.append(&mut vec![None; size]);
+                    is_file = true;
+                }
+            }
         }
+        Some(blocks)
     }
-    println!();
-}
 
-fn fragment_blocks(blocks: &mut Vec<Option<usize>>) -> Option<()> {
-    if blocks.len() < 1 {
-        return None;
+    pub(super) fn parse_files(input: &str) -> Option<Vec<(Option<usize>, usize)>> {
+        let mut is_file = true;
+        let mut file_index = 0;
+        let mut files = vec![];
+        for c in input.chars() {
+            if c == '\n' {
+                break;
+            }
+            let size = c.to_digit(10)?.try_into().ok()?;
+            match is_file {
+                true => {
+                    files.push((Some(file_index), size));
+                    is_file = false;
+                    file_index += 1;
+                }
+                false => {
+                    files.push((None, size));
+                    is_file = true;
+                }
+            }
+        }
+        Some(files)
     }
 
-    let mut cursor_left = 0;
-    let mut cursor_right = blocks.len() - 1;
-    while cursor_left < cursor_right {
-        if let None = blocks[cursor_right] {
-            cursor_right -= 1;
-            continue;
+    pub(super) fn fragment_blocks(blocks: &mut [Option<usize>]) -> Option<()> {
+        if blocks.is_empty() {
+            return None;
         }
-        match blocks[cursor_left] {
-            Some(_) => cursor_left += 1,
-            None => {
-                blocks.swap(cursor_left, cursor_right);
+
+        let mut cursor_left = 0;
+        let mut cursor_right = blocks.len() - 1;
+        while cursor_left < cursor_right {
+            if blocks[cursor_right].is_none() {
                 cursor_right -= 1;
+                continue;
+            }
+            match blocks[cursor_left] {
+                Some(_) => cursor_left += 1,
+                None => {
+                    blocks.swap(cursor_left, cursor_right);
+                    cursor_right -= 1;
+                }
             }
         }
+        Some(())
     }
-    Some(())
-}
 
-fn fragment_files(files: &mut Vec<(Option<usize>, usize)>) -> Option<()> {
-    if files.len() < 1 {
-        return None;
-    }
+    pub(super) fn fragment_files(files: &mut Vec<(Option<usize>, usize)>) -> Option<()> {
+        if files.is_empty() {
+            return None;
+        }
 
-    let mut cursor_left = 0;
-    let mut cursor_right = files.len() - 1;
-    while 0 < cursor_right {
-        cursor_left = 0;
-        while cursor_left < cursor_right {
-            // print!("({cursor_left}, {cursor_right})\t");
-            // print_files(files.clone());
-            let (index_right, size_right) = files[cursor_right];
-            if let None = index_right {
-                break;
-            }
-            let (index_left, size_left) = files[cursor_left];
-            if let Some(_) = index_left {
-                cursor_left += 1;
-                continue;
-            }
-            match size_left.cmp(&size_right) {
-                std::cmp::Ordering::Less => cursor_left += 1,
-                std::cmp::Ordering::Equal => {
-                    files.swap(cursor_left, cursor_right);
+        let mut cursor_right = files.len() - 1;
+        while 0 < cursor_right {
+            let mut cursor_left = 0;
+            while cursor_left < cursor_right {
+                let (index_right, size_right) = files[cursor_right];
+                if index_right.is_none() {
                     break;
                 }
-                std::cmp::Ordering::Greater => {
-                    // split free file into two free files
-                    files[cursor_left].1 = size_right;
-                    files.insert(
-                        cursor_left + 1,
-                        (
-                            None,
-                            match size_left.checked_sub(size_right) {
-                                Some(v) => v,
-                                None => {
-                                    println!("can't substract");
-                                    return None;
-                                }
-                            },
-                        ),
-                    );
-                    files.swap(cursor_left, cursor_right + 1);
-                    break;
+                let (index_left, size_left) = files[cursor_left];
+                if index_left.is_some() {
+                    cursor_left += 1;
+                    continue;
+                }
+                match size_left.cmp(&size_right) {
+                    std::cmp::Ordering::Less => cursor_left += 1,
+                    std::cmp::Ordering::Equal => {
+                        files.swap(cursor_left, cursor_right);
+                        break;
+                    }
+                    std::cmp::Ordering::Greater => {
+                        // split free file into two free files
+                        files[cursor_left].1 = size_right;
+                        files.insert(cursor_left + 1, (None, size_left - size_right));
+                        files.swap(cursor_left, cursor_right + 1);
+                        break;
+                    }
                 }
             }
+            cursor_right -= 1;
         }
-        cursor_right -= 1;
+        Some(())
     }
-    Some(())
-}
 
-fn checksum_blocks(blocks: Vec<Option<usize>>) -> usize {
-    let mut sum = 0;
-    for i in 0..blocks.len() {
-        if let Some(v) = blocks[i] {
-            sum += i * v;
+    pub(super) fn checksum_blocks(blocks: Vec<Option<usize>>) -> usize {
+        let mut sum = 0;
+        for (i, block) in blocks.into_iter().enumerate() {
+            if let Some(v) = block {
+                sum += i * v;
+            }
         }
+        sum
     }
-    sum
-}
 
-fn checksum_files(files: Vec<(Option<usize>, usize)>) -> usize {
-    let mut sum = 0;
-    let mut block_position = 0;
-    for (index, size) in files {
-        for _ in 0..size {
-            if let Some(v) = index {
-                sum += block_position * v;
+    pub(super) fn checksum_files(files: Vec<(Option<usize>, usize)>) -> usize {
+        let mut sum = 0;
+        let mut block_position = 0;
+        for (index, size) in files {
+            for _ in 0..size {
+                if let Some(v) = index {
+                    sum += block_position * v;
+                }
+                block_position += 1;
             }
-            block_position += 1;
         }
+        sum
     }
-    sum
 }
 
 #[cfg(test)]
 mod tests {
+    use advent_of_code::ranges::RangeSet;
+
     use super::*;
 
+    /// A disk map with `len` pseudo-random digits.
+    fn generate(len: usize, mut seed: u64) -> String {
+        (0..len)
+            .map(|_| {
+                seed = seed
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                char::from(b'0' + (seed >> 33) as u8 % 10)
+            })
+            .collect()
+    }
+
+    fn reference(input: &str) -> (usize, usize) {
+        let mut blocks = reference::parse_blocks(input).unwrap();
+        reference::fragment_blocks(&mut blocks).unwrap();
+        let mut files = reference::parse_files(input).unwrap();
+        reference::fragment_files(&mut files).unwrap();
+        (
+            reference::checksum_blocks(blocks),
+            reference::checksum_files(files),
+        )
+    }
+
+    /// Whole-file compaction on a [`RangeSet`] of free blocks. The set merges gaps that touch, so this only
+    /// agrees with the puzzle for inputs in which every file has blocks.
+    fn compact_on_ranges(input: &str) -> usize {
+        let mut files = vec![];
+        let mut free = RangeSet::new();
+        let mut start = 0;
+        for (i, c) in input.trim().bytes().enumerate() {
+            let len = i64::from(c - b'0');
+            if i % 2 == 0 {
+                files.push(start..start + len);
+            } else {
+                free.insert(start..start + len);
+            }
+            start += len;
+        }
+
+        let mut checksum = 0;
+        for (id, file) in files.into_iter().enumerate().rev() {
+            let len = file.end - file.start;
+            let mut blocks = file.clone();
+            if let Some(gap) = free.first_fit(len).filter(|gap| gap.start < file.start) {
+                free.remove(gap.start..gap.start + len);
+                free.insert(file);
+                blocks = gap.start..gap.start + len;
+            }
+            checksum += blocks.map(|block| block as usize * id).sum::<usize>();
+        }
+        checksum
+    }
+
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(1928));
+        assert_eq!(result, Ok(1928));
     }
 
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(2858));
+        assert_eq!(result, Ok(2858));
+    }
+
+    #[test]
+    fn test_matches_reference() {
+        let example = advent_of_code::template::read_file("examples", DAY);
+        let inputs = [
+            example,
+            "1".into(),
+            "10".into(),
+            "101".into(),
+            "9053".into(),
+        ];
+        let generated = (1..20).map(|seed| generate(seed * 97, seed as u64));
+        for input in inputs.into_iter().chain(generated) {
+            let expected = reference(&input);
+            assert_eq!(
+                (part_one(&input), part_two(&input)),
+                (Ok(expected.0), Ok(expected.1)),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_large_input() {
+        let input = generate(20_000, 42);
+        let expected = reference(&input);
+        assert_eq!(
+            (part_one(&input), part_two(&input)),
+            (Ok(expected.0), Ok(expected.1))
+        );
+    }
+
+    #[test]
+    #[ignore = "slow in debug builds, run with `cargo test --release --bin 09 -- --ignored`"]
+    fn test_millions_of_digits() {
+        let input: String = generate(2_000_000, 42)
+            .char_indices()
+            .map(|(i, c)| if i % 2 == 0 && c == '0' { '1' } else { c })
+            .collect();
+        let mut blocks = reference::parse_blocks(&input).unwrap();
+        reference::fragment_blocks(&mut blocks).unwrap();
+        assert_eq!(part_one(&input), Ok(reference::checksum_blocks(blocks)));
+        assert_eq!(part_two(&input), Ok(compact_on_ranges(&input)));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = part_one("12x4").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 3));
     }
 }
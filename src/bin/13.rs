@@ -1,98 +1,159 @@
-use regex::Regex;
+use advent_of_code::aoc_debug;
+use advent_of_code::math::{egcd, solve_2x2, MathError};
+use advent_of_code::parse::{self, Line, ParseError};
 
 advent_of_code::solution!(13);
 
-const BUTTON_MATCHER: &str = r".*X.(?<x>\d+),.Y.(?<y>\d+)";
-const BIG_NUMBER: f64 = 10000000000000_f64;
+const BIG_NUMBER: i128 = 10_000_000_000_000;
+
+/// Tokens needed for a press of button A and button B.
+const COST_A: i128 = 3;
+const COST_B: i128 = 1;
+
+pub fn part_one(input: &str) -> Result<i128, ParseError> {
+    Ok(total_tokens(&parse_machines(input)?, 0))
+}
+
+pub fn part_two(input: &str) -> Result<i128, ParseError> {
+    Ok(total_tokens(&parse_machines(input)?, BIG_NUMBER))
+}
+
+fn total_tokens(machines: &[Machine], offset: i128) -> i128 {
+    machines
+        .iter()
+        .filter_map(|machine| {
+            let presses = machine.cheapest_presses(offset);
+            match presses {
+                Some((a, b)) => aoc_debug!("{machine:?}\n\ta: {a}, b: {b} is possible !"),
+                None => aoc_debug!("{machine:?}\n\tis a scam..."),
+            }
+            presses.map(|(a, b)| a * COST_A + b * COST_B)
+        })
+        .sum()
+}
 
 #[derive(Debug)]
-struct Equation {
-    x: f64,
-    y: f64,
-    x_a: f64,
-    x_b: f64,
-    y_a: f64,
-    y_b: f64,
+struct Machine {
+    a: [i128; 2],
+    b: [i128; 2],
+    prize: [i128; 2],
 }
 
-impl Equation {
-    fn calculate_b(&self) -> f64 {
-        (self.y * self.x_a - self.y_a * self.x) / (-self.y_a * self.x_b + self.x_a * self.y_b)
+impl Machine {
+    /// The cheapest number of presses of A and B that reach the prize moved by `offset` on both axes.
+    fn cheapest_presses(&self, offset: i128) -> Option<(i128, i128)> {
+        let prize = self.prize.map(|p| p + offset);
+        let presses = match solve_2x2([[self.a[0], self.b[0]], [self.a[1], self.b[1]]], prize) {
+            Ok([a, b]) => Some((a, b)),
+            Err(MathError::Singular) => self.cheapest_parallel(prize),
+            Err(_) => None,
+        }?;
+        (presses.0 >= 0 && presses.1 >= 0).then_some(presses)
     }
 
-    fn calculate_a(&self, b: f64) -> f64 {
-        (self.x - b * self.x_b) / self.x_a
+    /// The buttons move along the same line (or not at all), so there can be many solutions.
+    fn cheapest_parallel(&self, prize: [i128; 2]) -> Option<(i128, i128)> {
+        let reaches = |a: i128, b: i128| (0..2).all(|k| a * self.a[k] + b * self.b[k] == prize[k]);
+        let presses = match (self.a, self.b) {
+            ([0, 0], [0, 0]) => Some((0, 0)),
+            ([0, 0], b) => presses_of(b, prize).map(|b| (0, b)),
+            (a, [0, 0]) => presses_of(a, prize).map(|a| (a, 0)),
+            (a, b) => {
+                // both buttons are non-zero and parallel, so they are non-zero on the same axis.
+                let k = usize::from(a[0] == 0);
+                cheapest_on_axis(a[k], b[k], prize[k])
+            }
+        }?;
+        reaches(presses.0, presses.1).then_some(presses)
     }
 }
 
-pub fn part_one(input: &str) -> Option<f64> {
-    let equations = parse_equations(input, 0_f64).unwrap();
-    let mut count = 0_f64;
-    for equation in equations {
-        println!("equation: {:?}", equation);
-        let b = equation.calculate_b();
-        let a = equation.calculate_a(b);
-        print!("\ta: {a}, b: {b}");
-        if a.floor() == a && b.floor() == b {
-            println!(" is possible !");
-            count += a * 3_f64 + b;
-        } else {
-            println!(" is a scam...");
-        }
-    }
-    Some(count)
+/// How many presses of a single button reach `prize`.
+fn presses_of(button: [i128; 2], prize: [i128; 2]) -> Option<i128> {
+    let k = usize::from(button[0] == 0);
+    (prize[k] % button[k] == 0).then(|| prize[k] / button[k])
 }
 
-pub fn part_two(input: &str) -> Option<f64> {
-    let equations = parse_equations(input, BIG_NUMBER).unwrap();
-    let mut count = 0_f64;
-    for equation in equations {
-        println!("equation: {:?}", equation);
-        let b = equation.calculate_b();
-        let a = equation.calculate_a(b);
-        print!("\ta: {a}, b: {b}");
-        if a.floor() == a && b.floor() == b {
-            println!(" is possible !");
-            count += a * 3_f64 + b;
+/// The cheapest non-negative `(a, b)` with `a * u + b * v = p`, for non-zero `u` and `v`.
+fn cheapest_on_axis(u: i128, v: i128, p: i128) -> Option<(i128, i128)> {
+    let (g, x, y) = egcd(u, v);
+    if p % g != 0 {
+        return None;
+    }
+    // all solutions are (a0 + t * s, b0 - t * r).
+    let (a0, b0) = (x * (p / g), y * (p / g));
+    let (s, r) = (v / g, u / g);
+
+    // a >= 0 and b >= 0 bound t from either side.
+    let (mut low, mut high) = (None::<i128>, None::<i128>);
+    let mut bound = |t: i128, is_lower: bool| {
+        if is_lower {
+            low = Some(low.map_or(t, |low| low.max(t)));
         } else {
-            println!(" is a scam...");
+            high = Some(high.map_or(t, |high| high.min(t)));
         }
+    };
+    if s > 0 {
+        bound(div_ceil(-a0, s), true);
+    } else {
+        bound(div_floor(-a0, s), false);
+    }
+    if r > 0 {
+        bound(div_floor(b0, r), false);
+    } else {
+        bound(div_ceil(b0, r), true);
     }
-    Some(count)
+
+    // the cost is linear in t, so the cheapest solution is at one of the bounds.
+    let slope = COST_A * s - COST_B * r;
+    let t = match (low, high) {
+        (Some(low), Some(high)) if low > high => return None,
+        (Some(low), _) if slope >= 0 => low,
+        (_, Some(high)) if slope <= 0 => high,
+        _ => return None,
+    };
+    Some((a0 + t * s, b0 - t * r))
 }
 
-fn parse_equations(input: &str, offset: f64) -> Result<Vec<Equation>, String> {
-    let mut equations = vec![];
-    let re = Regex::new(BUTTON_MATCHER).unwrap();
-    let mut lines = input.lines();
-    loop {
-        let line = match lines.next() {
-            Some(v) => v,
-            None => break,
-        };
-        let capture = re.captures(line).unwrap();
-        let x_a = capture.name("x").unwrap().as_str().parse().unwrap();
-        let y_a = capture.name("y").unwrap().as_str().parse().unwrap();
-        let line = lines.next().unwrap();
-        let capture = re.captures(line).unwrap();
-        let x_b = capture.name("x").unwrap().as_str().parse().unwrap();
-        let y_b = capture.name("y").unwrap().as_str().parse().unwrap();
-        let line = lines.next().unwrap();
-        let capture = re.captures(line).unwrap();
-        let x = offset + capture.name("x").unwrap().as_str().parse::<f64>().unwrap();
-        let y = offset + capture.name("y").unwrap().as_str().parse::<f64>().unwrap();
-        equations.push(Equation {
-            x,
-            y,
-            x_a,
-            x_b,
-            y_a,
-            y_b,
-        });
-        lines.next();
+fn div_floor(a: i128, b: i128) -> i128 {
+    let q = a / b;
+    if a % b != 0 && (a < 0) != (b < 0) {
+        q - 1
+    } else {
+        q
     }
+}
+
+fn div_ceil(a: i128, b: i128) -> i128 {
+    -div_floor(-a, b)
+}
 
-    Ok(equations)
+fn parse_machines(input: &str) -> Result<Vec<Machine>, ParseError> {
+    parse::blocks(input)
+        .iter()
+        .map(|block| {
+            let lines: Vec<Line> = block.lines().collect();
+            let [a, b, prize] = lines.as_slice() else {
+                return Err(ParseError {
+                    line: block.first_line,
+                    column: 1,
+                    message: format!("expected 3 lines per machine, found {}", lines.len()),
+                });
+            };
+            Ok(Machine {
+                a: parse_line(a, "Button A:")?,
+                b: parse_line(b, "Button B:")?,
+                prize: parse_line(prize, "Prize:")?,
+            })
+        })
+        .collect()
+}
+
+fn parse_line(line: &Line, prefix: &str) -> Result<[i128; 2], ParseError> {
+    if !line.text.starts_with(prefix) {
+        return Err(line.error(1, format!("expected \"{prefix}\"")));
+    }
+    Ok(line.fixed::<2>()?.map(i128::from))
 }
 
 #[cfg(test)]
@@ -102,12 +163,49 @@ mod tests {
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(480_f64));
+        assert_eq!(result, Ok(480));
     }
 
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(875318608908_f64));
+        assert_eq!(result, Ok(875318608908));
+    }
+
+    #[test]
+    fn test_parallel_buttons() {
+        let machine = |a, b, prize| Machine { a, b, prize };
+        // three presses of B are cheaper than nine of A.
+        assert_eq!(
+            machine([1, 1], [3, 3], [9, 9]).cheapest_presses(0),
+            Some((0, 3))
+        );
+        // B is cheaper per step, but 10 is not a multiple of 3.
+        assert_eq!(
+            machine([2, 2], [3, 3], [10, 10]).cheapest_presses(0),
+            Some((2, 2))
+        );
+        assert_eq!(machine([2, 2], [4, 4], [7, 7]).cheapest_presses(0), None);
+        assert_eq!(machine([1, 1], [2, 2], [4, 5]).cheapest_presses(0), None);
+        assert_eq!(
+            machine([0, 0], [2, 3], [4, 6]).cheapest_presses(0),
+            Some((0, 2))
+        );
+        assert_eq!(
+            machine([0, 0], [0, 0], [0, 0]).cheapest_presses(0),
+            Some((0, 0))
+        );
+        assert_eq!(machine([0, 0], [0, 0], [1, 0]).cheapest_presses(0), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let input = "Button A: X+94, Y+34\nButton B: X+22, Y+67\nPrize: X=8400, Y=5400\n\n\
+                     Button A: X+26, Y+66\nButton B: X+67\nPrize: X=12748, Y=12176\n";
+        let error = part_one(input).unwrap_err();
+        assert_eq!(error.line, 6);
+
+        let error = part_one("Button A: X+1, Y+2\nPrize: X=1, Y=2\n").unwrap_err();
+        assert_eq!(error.line, 1);
     }
 }
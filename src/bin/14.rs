@@ -1,141 +1,187 @@
-use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::{self, Display};
 
-use array2d::Array2D;
-use regex::Regex;
+use advent_of_code::aoc_debug;
+use advent_of_code::grid::Grid;
+use advent_of_code::math::{crt, MathError};
+use advent_of_code::parse::{self, ParseError};
+use advent_of_code::template::debug;
+use advent_of_code::viz::{self, Cell, Color, Frame};
 
 advent_of_code::solution!(14);
 
-pub fn part_one(input: &str) -> Option<i64> {
-    let mut robots = Robot::parse(input);
-    let dimensions = match robots.len() {
-        12 => DIMENSIONS_SMALL,
-        _ => DIMENSIONS_BIG,
-    };
-
-    Robot::all_step(&mut robots, 100, dimensions, false);
-    Robot::all_print(&mut robots, dimensions);
-    let quadrants = Robot::quadrants(&mut robots, dimensions);
-    let factor = quadrants.0 * quadrants.1 * quadrants.2 * quadrants.3;
-    Some(factor)
+pub fn part_one(input: &str) -> Result<i64, RobotError> {
+    safety_factor(&parse_input(input)?, DIMENSIONS)
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let mut robots = Robot::parse(input);
-    let dimensions = match robots.len() {
-        12 => DIMENSIONS_SMALL,
-        _ => DIMENSIONS_BIG,
-    };
-
-    let (second, score) = Robot::all_step(&mut robots, 10000, dimensions, true);
-    let second = second + 1; //to account for counting seconds from 1
-    let mut robots = Robot::parse(input);
-    Robot::all_step(&mut robots, second, dimensions, false);
-    Robot::all_print(&mut robots, dimensions);
-    println!("after {} seconds, score: {}", second, score);
-    Some(second)
+pub fn part_two(input: &str) -> Result<i64, RobotError> {
+    find_tree(&parse_input(input)?, DIMENSIONS)
 }
 
-const DIMENSIONS_SMALL: (i32, i32) = (11, 7);
-const DIMENSIONS_BIG: (i32, i32) = (101, 103);
-const ROBOT_MATCHER: &str = r"p=(?<x>\d+),(?<y>\d+) v=(?<vx>[+-]?\d+),(?<vy>[+-]?\d+)";
+/// Width and height of the area the robots move in. The example uses (11, 7).
+const DIMENSIONS: (i64, i64) = (101, 103);
 
-#[derive(Debug)]
-struct Robot {
-    x: i32,
-    y: i32,
-    vx: i32,
-    vy: i32,
+#[derive(Debug, PartialEq)]
+pub enum RobotError {
+    Parse(ParseError),
+    /// The seconds at which x and y bunch up don't combine into one second.
+    NoTree {
+        x_second: i64,
+        y_second: i64,
+        dimensions: (i64, i64),
+        source: MathError,
+    },
+    /// An area with this width or height can't be drawn.
+    Size(i64),
 }
 
-impl Robot {
-    fn step(&mut self, dimensions: (i32, i32)) {
-        self.x += self.vx;
-        if self.x < 0 {
-            self.x = dimensions.0 + self.x;
-        } else if self.x >= dimensions.0 {
-            self.x = self.x - dimensions.0;
+impl Display for RobotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RobotError::Parse(e) => write!(f, "{e}"),
+            RobotError::NoTree {
+                x_second,
+                y_second,
+                dimensions: (width, height),
+                ..
+            } => write!(
+                f,
+                "couldn't combine {x_second} (mod {width}) and {y_second} (mod {height})"
+            ),
+            RobotError::Size(v) => write!(f, "can't draw an area of size {v}"),
         }
+    }
+}
 
-        self.y += self.vy;
-        if self.y < 0 {
-            self.y = dimensions.1 + self.y;
-        } else if self.y >= dimensions.1 {
-            self.y = self.y - dimensions.1;
+impl Error for RobotError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RobotError::NoTree { source, .. } => Some(source),
+            RobotError::Parse(_) | RobotError::Size(_) => None,
         }
     }
+}
 
-    fn parse(input: &str) -> Vec<Robot> {
-        let mut robots = vec![];
-        let re = Regex::new(ROBOT_MATCHER).unwrap();
-        for line in input.lines() {
-            let capture = re.captures(line).unwrap();
-            robots.push(Robot {
-                x: capture.name("x").unwrap().as_str().parse().unwrap(),
-                y: capture.name("y").unwrap().as_str().parse().unwrap(),
-                vx: capture.name("vx").unwrap().as_str().parse().unwrap(),
-                vy: capture.name("vy").unwrap().as_str().parse().unwrap(),
-            });
-        }
-        robots
+impl From<ParseError> for RobotError {
+    fn from(e: ParseError) -> Self {
+        RobotError::Parse(e)
     }
+}
 
-    fn all_step(
-        robots: &mut Vec<Robot>,
-        seconds: u32,
-        dimensions: (i32, i32),
-        calculate_score: bool,
-    ) -> (u32, i64) {
-        let mut maximum: i64 = 0;
-        let mut second = 0;
-        for i in 0..seconds {
-            for robot in &mut *robots {
-                robot.step(dimensions);
-            }
-            if calculate_score {
-                let score = Robot::tree_score(robots);
-                if score > maximum {
-                    maximum = score;
-                    second = i;
-                }
+fn safety_factor(robots: &[Robot], dimensions: (i64, i64)) -> Result<i64, RobotError> {
+    let robots: Vec<Robot> = robots
+        .iter()
+        .map(|robot| robot.after(100, dimensions))
+        .collect();
+    show(&robots, dimensions, "after 100 seconds")?;
+    let quadrants = Robot::quadrants(&robots, dimensions);
+    let factor = quadrants.0 * quadrants.1 * quadrants.2 * quadrants.3;
+    Ok(factor)
+}
+
+/// The first second at which the robots form a picture, i.e. bunch up on both axes.
+///
+/// x positions repeat every `width` seconds and y positions every `height` seconds, so the second with the
+/// smallest variance of x and the one with the smallest variance of y are found separately and combined with
+/// the Chinese remainder theorem. The picture is pushed to the visualization, so `--viz-out` saves it as an image.
+fn find_tree(robots: &[Robot], dimensions: (i64, i64)) -> Result<i64, RobotError> {
+    let (width, height) = dimensions;
+
+    let x_second = min_variance(width, |t| {
+        robots.iter().map(move |r| r.after(t, dimensions).x)
+    });
+    let y_second = min_variance(height, |t| {
+        robots.iter().map(move |r| r.after(t, dimensions).y)
+    });
+    aoc_debug!("x bunches up after {x_second} seconds, y after {y_second} seconds");
+    let (second, _) =
+        crt([(x_second, width), (y_second, height)]).map_err(|source| RobotError::NoTree {
+            x_second,
+            y_second,
+            dimensions,
+            source,
+        })?;
+
+    let robots: Vec<Robot> = robots.iter().map(|r| r.after(second, dimensions)).collect();
+    show(&robots, dimensions, &format!("after {second} seconds"))?;
+    Ok(second)
+}
+
+/// Print the robots when debugging and push them to the visualization.
+fn show(robots: &[Robot], dimensions: (i64, i64), caption: &str) -> Result<(), RobotError> {
+    if debug::enabled() || viz::enabled() {
+        let frame = Robot::frame(robots, dimensions)?.caption(caption);
+        aoc_debug!("{frame}");
+        viz::push(&frame);
+    }
+    Ok(())
+}
+
+fn parse_input(input: &str) -> Result<Vec<Robot>, ParseError> {
+    parse::lines(input)
+        .map(|line| {
+            let [x, y, vx, vy] = line.fixed()?;
+            Ok(Robot { x, y, vx, vy })
+        })
+        .collect()
+}
+
+/// The second in `0..period` at which `positions` have the smallest variance. Ties go to the earliest second.
+fn min_variance<I: Iterator<Item = i64>>(period: i64, positions: impl Fn(i64) -> I) -> i64 {
+    (0..period)
+        .min_by_key(|&t| {
+            let (mut n, mut sum, mut squares) = (0, 0, 0);
+            for p in positions(t) {
+                n += 1;
+                sum += p;
+                squares += p * p;
             }
+            // n² times the variance, which keeps it an integer.
+            n * squares - sum * sum
+        })
+        .unwrap_or(0)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Robot {
+    x: i64,
+    y: i64,
+    vx: i64,
+    vy: i64,
+}
+
+impl Robot {
+    /// The robot after `seconds` seconds, wrapping around the edges.
+    fn after(&self, seconds: i64, dimensions: (i64, i64)) -> Robot {
+        Robot {
+            x: (self.x + self.vx * seconds).rem_euclid(dimensions.0),
+            y: (self.y + self.vy * seconds).rem_euclid(dimensions.1),
+            ..*self
         }
-        (second, maximum)
     }
 
-    fn all_print(robots: &mut Vec<Robot>, dimensions: (i32, i32)) {
-        let mut grid = Array2D::filled_with(
-            0,
-            usize::try_from(dimensions.1).unwrap(),
-            usize::try_from(dimensions.0).unwrap(),
-        );
+    fn frame(robots: &[Robot], dimensions: (i64, i64)) -> Result<Frame, RobotError> {
+        let size = |v: i64| usize::try_from(v).map_err(|_| RobotError::Size(v));
+        let mut counts = Grid::new(size(dimensions.0)?, size(dimensions.1)?, 0_u32);
         for robot in robots {
-            let cell = match grid.get_mut(
-                usize::try_from(robot.y).unwrap(),
-                usize::try_from(robot.x).unwrap(),
-            ) {
-                Some(v) => v,
-                None => {
-                    println!(
-                        "couldn't get from grid for {:?}, dimensions: {:?}",
-                        robot, dimensions
-                    );
-                    return;
+            let pos = (usize::try_from(robot.x), usize::try_from(robot.y));
+            if let (Ok(x), Ok(y)) = pos {
+                if let Some(count) = counts.get_mut((x, y)) {
+                    *count += 1;
                 }
-            };
-            *cell += 1;
+            }
         }
-        for row in grid.rows_iter() {
-            for cell in row {
-                match cell {
-                    0 => print!("."),
-                    _ => print!("{cell}"),
-                }
+        let mut frame = Frame::new(counts.width(), counts.height());
+        for ((x, y), count) in counts.iter() {
+            if *count > 0 {
+                let digit = char::from_digit(u32::min(*count, 9), 10).unwrap_or('9');
+                frame.set(x, y, Cell::new(digit).fg(Color::GREEN));
             }
-            println!("");
         }
+        Ok(frame)
     }
 
-    fn quadrants(robots: &mut Vec<Robot>, dimensions: (i32, i32)) -> (i64, i64, i64, i64) {
+    fn quadrants(robots: &[Robot], dimensions: (i64, i64)) -> (i64, i64, i64, i64) {
         let mut quadrants = (0, 0, 0, 0);
         for robot in robots {
             if robot.x < dimensions.0 / 2 {
@@ -158,35 +204,67 @@ impl Robot {
         }
         quadrants
     }
-
-    fn tree_score(robots: &Vec<Robot>) -> i64 {
-        let mut score = 0;
-        let mut set = HashSet::new();
-        for robot in robots {
-            set.insert((robot.x, robot.y));
-        }
-        for robot in robots {
-            if let Some(_) = set.get(&(robot.x, robot.y - 1)) {
-                score += 1;
-            }
-        }
-        score
-    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Solves a part in the 11 by 7 area of the example.
+    fn solve(
+        input: &str,
+        part: impl Fn(&[Robot], (i64, i64)) -> Result<i64, RobotError>,
+    ) -> Result<i64, RobotError> {
+        part(&parse_input(input)?, (11, 7))
+    }
+
     #[test]
     fn test_part_one() {
-        let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(12));
+        let result = solve(
+            &advent_of_code::template::read_file("examples", DAY),
+            safety_factor,
+        );
+        assert_eq!(result, Ok(12));
     }
 
     #[test]
     fn test_part_two() {
-        let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(40));
+        // robots that all meet around (5, 3) after 40 seconds.
+        let second: i64 = 40;
+        let (width, height) = (11, 7);
+        let robots: String = (0..12)
+            .map(|i| {
+                let (vx, vy) = (i % 11 - 5, i * 3 % 7 - 3);
+                let x = (5 + i % 2 - vx * second).rem_euclid(width);
+                let y = (3 + i % 2 - vy * second).rem_euclid(height);
+                format!("p={x},{y} v={vx},{vy}\n")
+            })
+            .collect();
+        assert_eq!(solve(&robots, find_tree), Ok(second));
+    }
+
+    #[test]
+    fn test_wraps_around() {
+        let robot = Robot {
+            x: 0,
+            y: 0,
+            vx: 1,
+            vy: 1,
+        };
+        assert_eq!(
+            robot.after(101, DIMENSIONS),
+            Robot {
+                x: 0,
+                y: 101,
+                ..robot
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let result = parse_input("p=0,4 v=3,-3\np=6,3 v=-1\n");
+        assert_eq!(result.map_err(|e| e.line), Err(2));
+        assert!(matches!(part_one("p=0,4 v=3\n"), Err(RobotError::Parse(_))));
     }
 }
@@ -1,43 +1,34 @@
-use core::fmt;
-
+use advent_of_code::aoc_debug;
+use advent_of_code::geometry::Direction;
+use advent_of_code::viz::{self, Cell, Color, Frame};
 use array2d::Array2D;
 
 advent_of_code::solution!(15);
 
-pub fn part_one(input: &str) -> Option<usize> {
-    let (mut warehouse, directions) = match parse_input(input, false) {
-        Ok(v) => v,
-        Err(e) => {
-            println!("couldn't parse input: {e}");
-            return None;
-        }
-    };
-    println!("initial state:");
-    warehouse.print_map();
-    for d in directions {
-        println!("\nmove {}:", d);
+pub fn part_one(input: &str) -> Result<usize, String> {
+    let (mut warehouse, directions) =
+        parse_input(input, false).map_err(|e| format!("couldn't parse input: {e}"))?;
+    aoc_debug!("{}", warehouse.frame().caption("initial state"));
+    viz::push_with(|| warehouse.frame().caption("initial state"));
+    for (i, d) in directions.into_iter().enumerate() {
         warehouse.step(d);
-        warehouse.print_map();
+        aoc_debug!("{}", warehouse.frame().caption(format!("move {d}")));
+        viz::push_with(|| warehouse.frame().caption(format!("move {}: {d}", i + 1)));
     }
-    Some(warehouse.sum_box_coordinates())
+    Ok(warehouse.sum_box_coordinates())
 }
 
-pub fn part_two(input: &str) -> Option<usize> {
-    let (mut warehouse, directions) = match parse_input(input, true) {
-        Ok(v) => v,
-        Err(e) => {
-            println!("couldn't parse input: {e}");
-            return None;
-        }
-    };
-    println!("initial state:");
-    warehouse.print_map();
-    for d in directions {
-        println!("\nmove {}:", d);
+pub fn part_two(input: &str) -> Result<usize, String> {
+    let (mut warehouse, directions) =
+        parse_input(input, true).map_err(|e| format!("couldn't parse input: {e}"))?;
+    aoc_debug!("{}", warehouse.frame().caption("initial state"));
+    viz::push_with(|| warehouse.frame().caption("initial state"));
+    for (i, d) in directions.into_iter().enumerate() {
         warehouse.step(d);
-        warehouse.print_map();
+        aoc_debug!("{}", warehouse.frame().caption(format!("move {d}")));
+        viz::push_with(|| warehouse.frame().caption(format!("move {}: {d}", i + 1)));
     }
-    Some(warehouse.sum_box_coordinates())
+    Ok(warehouse.sum_box_coordinates())
 }
 
 #[derive(Clone)]
@@ -50,24 +41,18 @@ enum Tile {
     Robot,
 }
 
-impl fmt::Display for Tile {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Tile::Floor => '.',
-                Tile::Wall => '#',
-                Tile::Box => 'O',
-                Tile::BoxLeft => '[',
-                Tile::BoxRight => ']',
-                Tile::Robot => '@',
-            }
-        )
+impl Tile {
+    fn symbol(&self) -> char {
+        match self {
+            Tile::Floor => '.',
+            Tile::Wall => '#',
+            Tile::Box => 'O',
+            Tile::BoxLeft => '[',
+            Tile::BoxRight => ']',
+            Tile::Robot => '@',
+        }
     }
-}
 
-impl Tile {
     fn new(tile: char) -> Option<Tile> {
         match tile {
             '#' => Some(Tile::Wall),
@@ -79,51 +64,6 @@ impl Tile {
     }
 }
 
-#[derive(Clone, Debug)]
-enum Direction {
-    East,
-    South,
-    West,
-    North,
-}
-
-impl fmt::Display for Direction {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Direction::East => '>',
-                Direction::South => 'v',
-                Direction::West => '<',
-                Direction::North => '^',
-            }
-        )
-    }
-}
-
-impl Direction {
-    fn step(&self, row: usize, column: usize) -> Option<(usize, usize)> {
-        let (row, column) = match self {
-            Direction::East => (Some(row), column.checked_add(1)),
-            Direction::South => (row.checked_add(1), Some(column)),
-            Direction::West => (Some(row), column.checked_sub(1)),
-            Direction::North => (row.checked_sub(1), Some(column)),
-        };
-        Some((row?, column?))
-    }
-
-    fn new(input: char) -> Option<Direction> {
-        match input {
-            '>' => Some(Direction::East),
-            'v' => Some(Direction::South),
-            '<' => Some(Direction::West),
-            '^' => Some(Direction::North),
-            _ => None,
-        }
-    }
-}
-
 struct Robot {
     x: usize,
     y: usize,
@@ -135,26 +75,26 @@ struct Warehouse {
 }
 
 impl Warehouse {
-    fn print_map(&self) {
-        let mut y = 0;
-        for row in self.map.rows_iter() {
-            let mut x = 0;
-            for tile in row {
-                if x == self.robot.x && y == self.robot.y {
-                    print!("@");
-                } else {
-                    print!("{tile}");
-                }
-                x += 1;
+    fn frame(&self) -> Frame {
+        let mut frame = Frame::new(self.map.num_columns(), self.map.num_rows());
+        for (y, row) in self.map.rows_iter().enumerate() {
+            for (x, tile) in row.enumerate() {
+                let cell = Cell::new(tile.symbol());
+                let cell = match tile {
+                    Tile::Wall => cell.fg(Color::GREY),
+                    Tile::Box | Tile::BoxLeft | Tile::BoxRight => cell.fg(Color::YELLOW),
+                    Tile::Floor | Tile::Robot => cell,
+                };
+                frame.set(x, y, cell);
             }
-            println!("");
-            y += 1;
         }
+        frame.set(self.robot.x, self.robot.y, Cell::new('@').fg(Color::RED));
+        frame
     }
 
     fn step(&mut self, direction: Direction) {
-        let (next_y, next_x) = direction.step(self.robot.y, self.robot.x).unwrap();
-        if self.check_push(next_x, next_y, direction.clone()) {
+        let (next_x, next_y) = direction.step((self.robot.x, self.robot.y)).unwrap();
+        if self.check_push(next_x, next_y, direction) {
             self.push_box(next_x, next_y, direction);
             self.robot = Robot {
                 x: next_x,
@@ -164,7 +104,7 @@ impl Warehouse {
     }
 
     fn check_push(&mut self, x: usize, y: usize, d: Direction) -> bool {
-        let (next_y, next_x) = match d.step(y, x) {
+        let (next_x, next_y) = match d.step((x, y)) {
             Some(v) => v,
             None => return false,
         };
@@ -178,8 +118,7 @@ impl Warehouse {
                 Direction::East => self.check_push(next_x + 1, next_y, d),
                 _ => {
                     // check left and right side of the box
-                    self.check_push(next_x, next_y, d.clone())
-                        && self.check_push(next_x + 1, next_y, d)
+                    self.check_push(next_x, next_y, d) && self.check_push(next_x + 1, next_y, d)
                 }
             },
             Tile::BoxRight => match d {
@@ -188,8 +127,7 @@ impl Warehouse {
                 Direction::East => self.check_push(next_x, next_y, d),
                 _ => {
                     // check left and right side of the box
-                    self.check_push(next_x - 1, next_y, d.clone())
-                        && self.check_push(next_x, next_y, d)
+                    self.check_push(next_x - 1, next_y, d) && self.check_push(next_x, next_y, d)
                 }
             },
             _ => false,
@@ -197,7 +135,7 @@ impl Warehouse {
     }
 
     fn push_box(&mut self, x: usize, y: usize, d: Direction) {
-        let (next_y, next_x) = d.step(y, x).unwrap();
+        let (next_x, next_y) = d.step((x, y)).unwrap();
         match self.map.get(y, x).unwrap() {
             Tile::Box => {
                 self.push_box(next_x, next_y, d);
@@ -218,7 +156,7 @@ impl Warehouse {
                     self.map.set(next_y, next_x + 1, Tile::BoxRight).unwrap();
                 }
                 _ => {
-                    self.push_box(next_x, next_y, d.clone());
+                    self.push_box(next_x, next_y, d);
                     self.push_box(next_x + 1, next_y, d);
                     self.map.set(y, x, Tile::Floor).unwrap();
                     self.map.set(y, x + 1, Tile::Floor).unwrap();
@@ -240,7 +178,7 @@ impl Warehouse {
                     self.map.set(next_y, next_x, Tile::BoxRight).unwrap();
                 }
                 _ => {
-                    self.push_box(next_x - 1, next_y, d.clone());
+                    self.push_box(next_x - 1, next_y, d);
                     self.push_box(next_x, next_y, d);
                     self.map.set(y, x - 1, Tile::Floor).unwrap();
                     self.map.set(y, x, Tile::Floor).unwrap();
@@ -254,17 +192,13 @@ impl Warehouse {
 
     fn sum_box_coordinates(&self) -> usize {
         let mut sum = 0;
-        let mut y = 0;
-        for row in self.map.rows_iter() {
-            let mut x = 0;
-            for tile in row {
+        for (y, row) in self.map.rows_iter().enumerate() {
+            for (x, tile) in row.enumerate() {
                 match tile {
                     Tile::Box | Tile::BoxLeft => sum += 100 * y + x,
                     _ => {}
                 }
-                x += 1;
             }
-            y += 1;
         }
         sum
     }
@@ -279,7 +213,7 @@ fn parse_input(input: &str, big_boxes: bool) -> Result<(Warehouse, Vec<Direction
     for line in input.lines() {
         let mut x = 0;
         if parsing_map {
-            if line == "" {
+            if line.is_empty() {
                 parsing_map = false;
                 continue;
             }
@@ -315,7 +249,7 @@ fn parse_input(input: &str, big_boxes: bool) -> Result<(Warehouse, Vec<Direction
             y += 1;
         } else {
             for c in line.chars() {
-                let direction = match Direction::new(c) {
+                let direction = match Direction::from_char(c) {
                     Some(v) => v,
                     None => return Err(format!("couldn't make new direction from {c}")),
                 };
@@ -339,12 +273,12 @@ mod tests {
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(10092));
+        assert_eq!(result, Ok(10092));
     }
 
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(9021));
+        assert_eq!(result, Ok(9021));
     }
 }
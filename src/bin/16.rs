@@ -1,39 +1,49 @@
-use std::{
-    cmp::Ordering,
-    collections::{BinaryHeap, HashMap},
-    fmt,
-};
+use std::fmt;
 
-use array2d::Array2D;
+use advent_of_code::aoc_debug;
+use advent_of_code::geometry::Direction;
+use advent_of_code::grid::{Grid, Pos};
+use advent_of_code::hash::FastSet;
+use advent_of_code::search;
+use advent_of_code::viz::{Cell, Color, Frame};
 
 advent_of_code::solution!(16);
 
-pub fn part_one(input: &str) -> Option<usize> {
-    let (grid, start, end) = match parse_grid(input) {
-        Ok(v) => v,
-        Err(e) => {
-            println!("couldn't parse grid: {e}");
-            return None;
-        }
-    };
-    let (distance, _) = search(&grid, start, end)?;
-    Some(distance)
-}
-
-pub fn part_two(input: &str) -> Option<usize> {
-    let (grid, start, end) = match parse_grid(input) {
-        Ok(v) => v,
-        Err(e) => {
-            println!("couldn't parse grid: {e}");
-            return None;
-        }
-    };
-    let (_, path) = search(&grid, start.clone(), end.clone())?;
-    let path = reconstruct_path(path, start.point, end)?;
-    Some(path.len())
-}
-
-#[derive(Clone)]
+/// Cost of a step forward and of a turn on the spot.
+const STEP_COST: usize = 1;
+const TURN_COST: usize = 1000;
+
+pub fn part_one(input: &str) -> Result<usize, String> {
+    let (grid, start, end) = parse_grid(input)?;
+    let (cost, _) = search::dijkstra(
+        start,
+        |point| next_states(&grid, point),
+        |point| point.pos() == end,
+    )
+    .ok_or("couldn't find a path to the end")?;
+    Ok(cost)
+}
+
+pub fn part_two(input: &str) -> Result<usize, String> {
+    let (grid, start, end) = parse_grid(input)?;
+    // every equal-cost predecessor of each (x, y, direction) is kept, and the end can be reached facing
+    // different directions.
+    let paths = search::dijkstra_all(
+        start,
+        |point| next_states(&grid, point),
+        |point| point.pos() == end,
+    )
+    .ok_or("couldn't find a path to the end")?;
+
+    let tiles = paths.tiles(Point::pos);
+    aoc_debug!(
+        "{}",
+        frame(&grid, &tiles).caption(format!("cost {}", paths.cost))
+    );
+    Ok(tiles.len())
+}
+
+#[derive(Clone, PartialEq)]
 enum Tile {
     Floor,
     Wall,
@@ -68,223 +78,59 @@ impl Tile {
     }
 }
 
-#[derive(Clone, Debug, Hash, Eq, PartialEq)]
-enum Direction {
-    East,
-    South,
-    West,
-    North,
-}
-
-impl fmt::Display for Direction {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Direction::East => '>',
-                Direction::South => 'v',
-                Direction::West => '<',
-                Direction::North => '^',
-            }
-        )
-    }
-}
-
-impl Direction {
-    fn step(&self, row: usize, column: usize) -> Option<(usize, usize)> {
-        let (row, column) = match self {
-            Direction::East => (Some(row), column.checked_add(1)),
-            Direction::South => (row.checked_add(1), Some(column)),
-            Direction::West => (Some(row), column.checked_sub(1)),
-            Direction::North => (row.checked_sub(1), Some(column)),
-        };
-        Some((row?, column?))
-    }
-
-    fn rotate_left(&self) -> Direction {
-        match self {
-            Direction::East => Direction::North,
-            Direction::South => Direction::East,
-            Direction::West => Direction::South,
-            Direction::North => Direction::West,
-        }
-    }
-
-    fn rotate_right(&self) -> Direction {
-        match self {
-            Direction::East => Direction::South,
-            Direction::South => Direction::West,
-            Direction::West => Direction::North,
-            Direction::North => Direction::East,
-        }
-    }
-}
-
-#[derive(Clone, Hash, Eq, PartialEq, Debug)]
+/// A reindeer's position and facing.
+#[derive(Clone, Copy, Hash, Eq, PartialEq, Debug)]
 struct Point {
     x: usize,
     y: usize,
     d: Direction,
 }
 
-#[derive(Hash, Eq, PartialEq, Clone)]
-struct State {
-    point: Point,
-    cost: usize,
-}
-
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other
-            .cost
-            .cmp(&self.cost)
-            .then_with(|| self.point.x.cmp(&other.point.x))
-            .then_with(|| self.point.y.cmp(&other.point.y))
-    }
-}
-
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+impl Point {
+    fn pos(&self) -> Pos {
+        (self.x, self.y)
     }
 }
 
-fn parse_grid(input: &str) -> Result<(Array2D<Tile>, State, Point), String> {
-    let mut y = 0;
-    let mut start = State {
-        point: Point {
-            x: 0,
-            y: 0,
-            d: Direction::East,
-        },
-        cost: 0,
-    };
-    let mut end = Point {
-        x: 0,
-        y: 0,
+/// The reindeer starts facing east.
+fn parse_grid(input: &str) -> Result<(Grid<Tile>, Point, Pos), String> {
+    let grid = Grid::parse(input, Tile::new).map_err(|e| format!("couldn't parse grid: {e}"))?;
+    let (x, y) = grid.find(&Tile::Start).ok_or("couldn't find the start")?;
+    let end = grid.find(&Tile::End).ok_or("couldn't find the end")?;
+    let start = Point {
+        x,
+        y,
         d: Direction::East,
     };
-    let mut rows = vec![];
-    for line in input.lines() {
-        let mut x = 0;
-        let mut row = vec![];
-        for tile in line.chars() {
-            let mut tile = match Tile::new(tile) {
-                Some(v) => v,
-                None => return Err(format!("couldn't make new tile from {tile}")),
-            };
-            if let Tile::Start = tile {
-                start = State {
-                    point: Point {
-                        x,
-                        y,
-                        d: Direction::East,
-                    },
-                    cost: 0,
-                };
-                tile = Tile::Floor;
-            } else if let Tile::End = tile {
-                end = Point {
-                    x,
-                    y,
-                    d: Direction::East,
-                };
-                tile = Tile::Floor;
-            }
-            row.push(tile);
-            x += 1;
-        }
-        rows.push(row);
-        y += 1;
-    }
-    let grid = Array2D::from_rows(&rows).unwrap();
     Ok((grid, start, end))
 }
 
-fn next_states(grid: &Array2D<Tile>, state: State) -> Vec<State> {
+/// The states after a step forward, if there is no wall, or after turning either way, and their costs.
+fn next_states(grid: &Grid<Tile>, point: &Point) -> Vec<(Point, usize)> {
     let mut states = vec![];
-    let (next_y, next_x) = state.point.d.step(state.point.y, state.point.x).unwrap();
-    if let Tile::Floor = grid.get(next_y, next_x).unwrap() {
-        states.push(State {
-            point: Point {
-                x: next_x,
-                y: next_y,
-                d: state.point.d.clone(),
-            },
-            cost: 1,
-        });
-    }
-    states.push(State {
-        point: Point {
-            x: state.point.x,
-            y: state.point.y,
-            d: state.point.d.rotate_left(),
-        },
-        cost: 1000,
-    });
-    states.push(State {
-        point: Point {
-            x: state.point.x,
-            y: state.point.y,
-            d: state.point.d.rotate_right(),
-        },
-        cost: 1000,
-    });
-    states
-}
-
-fn search(
-    grid: &Array2D<Tile>,
-    start: State,
-    end: Point,
-) -> Option<(usize, HashMap<Point, Point>)> {
-    let mut frontier = BinaryHeap::new();
-    let mut distance = HashMap::new();
-    let mut path = HashMap::new();
-
-    frontier.push(start.clone());
-    distance.insert(start.point.clone(), 0_usize);
-
-    while let Some(current_state) = frontier.pop() {
-        if current_state.point.x == end.x && current_state.point.y == end.y {
-            return Some((current_state.cost, path));
-        }
-        if current_state.cost > *distance.get(&current_state.point).unwrap() {
-            continue;
-        }
-        for mut next_state in next_states(grid, current_state.clone()) {
-            next_state.cost += current_state.cost;
-            if let Some(next_distance) = distance.get(&next_state.point) {
-                if next_state.cost > *next_distance {
-                    continue;
-                }
-            }
-            distance.insert(next_state.point.clone(), next_state.cost);
-            path.insert(next_state.point.clone(), current_state.point.clone());
-            frontier.push(next_state);
+    if let Some((x, y)) = point.d.step(point.pos()) {
+        if grid.get((x, y)).is_some_and(|tile| *tile != Tile::Wall) {
+            states.push((Point { x, y, ..*point }, STEP_COST));
         }
     }
-    None
+    for d in [point.d.rotate_left(), point.d.rotate_right()] {
+        states.push((Point { d, ..*point }, TURN_COST));
+    }
+    states
 }
 
-fn reconstruct_path(path: HashMap<Point, Point>, start: Point, end: Point) -> Option<Vec<Point>> {
-    let mut point = end;
-    let mut trail = Vec::new();
-    println!("path: {:?}", path);
-    loop {
-        println!("reconstructing at {:?}", point);
-        let previous_point = match path.get(&point) {
-            Some(v) => v,
-            None => break,
+/// The maze with the tiles of all best paths highlighted.
+fn frame(grid: &Grid<Tile>, tiles: &FastSet<Pos>) -> Frame {
+    let mut frame = Frame::new(grid.width(), grid.height());
+    for ((x, y), tile) in grid.iter() {
+        let cell = match tile {
+            _ if tiles.contains(&(x, y)) => Cell::new('O').fg(Color::RED),
+            Tile::Wall => Cell::new('#').fg(Color::GREY),
+            tile => Cell::new(tile.to_string().chars().next().unwrap_or('.')),
         };
-        trail.push(point.clone());
-        if point == start {
-            return Some(trail);
-        }
-        point = previous_point.clone();
+        frame.set(x, y, cell);
     }
-    None
+    frame
 }
 
 #[cfg(test)]
@@ -294,12 +140,21 @@ mod tests {
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(7036));
+        assert_eq!(result, Ok(7036));
     }
 
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, None);
+        assert_eq!(result, Ok(45));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(part_one("#####\n#S.x#\n#####\n").is_err());
+        assert_eq!(
+            part_one("#####\n#S..#\n#####\n"),
+            Err("couldn't find the end".into())
+        );
     }
 }
@@ -0,0 +1,188 @@
+/// Cycle detection for sequences of states, where each state is computed from the previous one.
+///
+/// [`brent`] and [`floyd`] only keep two states in memory and need `step` to be deterministic. [`detect`] and
+/// [`nth_state`] remember every state in a hash map, which needs fewer steps and works for sequences that end.
+/// [`nth_state`] skips whole cycles, for puzzles that ask for the state after a huge number of steps.
+use std::hash::Hash;
+
+use crate::hash::FastMap;
+
+/// A cycle in a sequence of states: state `start + length` is the same as state `start`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    /// Index of the first state on the cycle.
+    pub start: usize,
+    /// Number of states on the cycle.
+    pub length: usize,
+}
+
+impl Cycle {
+    /// The index of the first state that is the same as state `n`, i.e. an index below `start + length`.
+    #[must_use]
+    pub fn index_of(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+}
+
+/// Brent's algorithm. Needs fewer steps than [`floyd`].
+///
+/// Loops forever if the sequence has no cycle.
+pub fn brent<S: PartialEq + Clone>(initial: S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    let (mut power, mut length) = (1, 1);
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+
+    let mut tortoise = initial.clone();
+    let mut hare = initial;
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+    Cycle { start, length }
+}
+
+/// Floyd's tortoise and hare algorithm.
+///
+/// Loops forever if the sequence has no cycle.
+pub fn floyd<S: PartialEq + Clone>(initial: S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    let mut tortoise = step(&initial);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        let next = step(&hare);
+        hare = step(&next);
+    }
+
+    let mut tortoise = initial;
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    let mut length = 1;
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        length += 1;
+    }
+    Cycle { start, length }
+}
+
+/// Remember every state until one repeats. `step` returns `None` when the sequence ends, in which case there is
+/// no cycle.
+pub fn detect<S: Eq + Hash + Clone>(
+    initial: S,
+    mut step: impl FnMut(&S) -> Option<S>,
+) -> Option<Cycle> {
+    let mut seen = FastMap::default();
+    let mut state = initial;
+    for index in 0.. {
+        if let Some(start) = seen.insert(state.clone(), index) {
+            return Some(Cycle {
+                start,
+                length: index - start,
+            });
+        }
+        state = step(&state)?;
+    }
+    unreachable!()
+}
+
+/// State `n` of the sequence, where state 0 is `initial`. Stops stepping as soon as a cycle is found, so `n`
+/// can be far larger than the number of distinct states.
+pub fn nth_state<S: Eq + Hash + Clone>(initial: S, mut step: impl FnMut(&S) -> S, n: usize) -> S {
+    let mut seen = FastMap::default();
+    let mut states = vec![];
+    let mut state = initial;
+    for index in 0..n {
+        if let Some(start) = seen.insert(state.clone(), index) {
+            let cycle = Cycle {
+                start,
+                length: index - start,
+            };
+            return states.swap_remove(cycle.index_of(n));
+        }
+        let next = step(&state);
+        states.push(state);
+        state = next;
+    }
+    state
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{brent, detect, floyd, nth_state, Cycle};
+
+    /// 0, 1, 2, 3, 4, 5, 6, 7, 3, 4, ...
+    fn step(x: &u32) -> u32 {
+        if *x == 7 {
+            3
+        } else {
+            x + 1
+        }
+    }
+
+    const CYCLE: Cycle = Cycle {
+        start: 3,
+        length: 5,
+    };
+
+    #[test]
+    fn finds_cycles() {
+        assert_eq!(brent(0, step), CYCLE);
+        assert_eq!(floyd(0, step), CYCLE);
+        assert_eq!(detect(0, |x| Some(step(x))), Some(CYCLE));
+        assert_eq!(
+            brent(3, step),
+            Cycle {
+                start: 0,
+                length: 5
+            }
+        );
+        assert_eq!(
+            floyd(5, |x| *x),
+            Cycle {
+                start: 0,
+                length: 1
+            }
+        );
+    }
+
+    #[test]
+    fn detects_sequences_that_end() {
+        assert_eq!(detect(0, |x| (*x < 10).then_some(x + 1)), None);
+    }
+
+    #[test]
+    fn jumps_to_nth_state() {
+        assert_eq!(CYCLE.index_of(2), 2);
+        assert_eq!(CYCLE.index_of(8), 3);
+        assert_eq!(CYCLE.index_of(1_000_000_000), 5);
+        assert_eq!(nth_state(0, step, 2), 2);
+        assert_eq!(nth_state(0, step, 8), 3);
+        assert_eq!(nth_state(0, step, 1_000_000_000), 5);
+        assert_eq!(nth_state(0, step, 0), 0);
+    }
+}
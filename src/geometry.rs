@@ -0,0 +1,425 @@
+/// Directions, points and vectors on a 2D grid.
+///
+/// Coordinates follow [`crate::grid`]: `x` grows to the right and `y` grows down, so north is `(0, -1)` and
+/// rotating right turns clockwise on screen.
+use std::fmt::{self, Display};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use crate::grid::Pos;
+
+/// One of the four orthogonal directions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    /// All directions, clockwise starting north.
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    /// Parse an arrow: `^`, `>`, `v` or `<`.
+    #[must_use]
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            '^' => Some(Direction::North),
+            '>' => Some(Direction::East),
+            'v' => Some(Direction::South),
+            '<' => Some(Direction::West),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn to_char(self) -> char {
+        match self {
+            Direction::North => '^',
+            Direction::East => '>',
+            Direction::South => 'v',
+            Direction::West => '<',
+        }
+    }
+
+    #[must_use]
+    pub fn opposite(self) -> Self {
+        self.rotate_right().rotate_right()
+    }
+
+    /// Turn 90 degrees clockwise.
+    #[must_use]
+    pub fn rotate_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 4]
+    }
+
+    /// Turn 90 degrees counter-clockwise.
+    #[must_use]
+    pub fn rotate_left(self) -> Self {
+        Self::ALL[(self as usize + 3) % 4]
+    }
+
+    #[must_use]
+    pub fn is_horizontal(self) -> bool {
+        matches!(self, Direction::East | Direction::West)
+    }
+
+    #[must_use]
+    pub fn vector(self) -> Vector {
+        let (x, y) = self.delta();
+        Vector { x, y }
+    }
+
+    /// The offset of one step, e.g. for [`crate::grid::Grid::step`].
+    #[must_use]
+    pub fn delta(self) -> (i64, i64) {
+        match self {
+            Direction::North => (0, -1),
+            Direction::East => (1, 0),
+            Direction::South => (0, 1),
+            Direction::West => (-1, 0),
+        }
+    }
+
+    /// Take one step from a grid position. Returns `None` when stepping past the top or left edge; the bottom and
+    /// right edges are not checked, use [`Point::step_within`] or [`crate::grid::Grid::step`] for that.
+    #[must_use]
+    pub fn step(self, (x, y): Pos) -> Option<Pos> {
+        match self {
+            Direction::North => Some((x, y.checked_sub(1)?)),
+            Direction::East => Some((x.checked_add(1)?, y)),
+            Direction::South => Some((x, y.checked_add(1)?)),
+            Direction::West => Some((x.checked_sub(1)?, y)),
+        }
+    }
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+/// One of the eight orthogonal or diagonal directions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction8 {
+    /// All directions, clockwise starting north.
+    pub const ALL: [Direction8; 8] = [
+        Direction8::North,
+        Direction8::NorthEast,
+        Direction8::East,
+        Direction8::SouthEast,
+        Direction8::South,
+        Direction8::SouthWest,
+        Direction8::West,
+        Direction8::NorthWest,
+    ];
+
+    /// The four diagonal directions, clockwise starting north east.
+    pub const DIAGONALS: [Direction8; 4] = [
+        Direction8::NorthEast,
+        Direction8::SouthEast,
+        Direction8::SouthWest,
+        Direction8::NorthWest,
+    ];
+
+    #[must_use]
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 4) % 8]
+    }
+
+    /// Turn 45 degrees clockwise.
+    #[must_use]
+    pub fn rotate_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 8]
+    }
+
+    /// Turn 45 degrees counter-clockwise.
+    #[must_use]
+    pub fn rotate_left(self) -> Self {
+        Self::ALL[(self as usize + 7) % 8]
+    }
+
+    #[must_use]
+    pub fn is_diagonal(self) -> bool {
+        self as usize % 2 == 1
+    }
+
+    #[must_use]
+    pub fn vector(self) -> Vector {
+        let (x, y) = self.delta();
+        Vector { x, y }
+    }
+
+    #[must_use]
+    pub fn delta(self) -> (i64, i64) {
+        crate::grid::DELTAS8[self as usize]
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(direction: Direction) -> Self {
+        Self::ALL[direction as usize * 2]
+    }
+}
+
+/// A position with signed coordinates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+/// The difference between two points.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Vector {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+    #[must_use]
+    pub const fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    #[must_use]
+    pub fn manhattan(self, other: Point) -> i64 {
+        (self - other).manhattan()
+    }
+
+    /// Take one step. The result may be outside of any grid.
+    #[must_use]
+    pub fn step(self, direction: impl Into<Vector>) -> Self {
+        self + direction.into()
+    }
+
+    /// Take one step, returning `None` if the result is outside of `0..width` and `0..height`.
+    #[must_use]
+    pub fn step_within(
+        self,
+        direction: impl Into<Vector>,
+        width: usize,
+        height: usize,
+    ) -> Option<Self> {
+        let next = self.step(direction);
+        next.is_within(width, height).then_some(next)
+    }
+
+    #[must_use]
+    pub fn is_within(self, width: usize, height: usize) -> bool {
+        usize::try_from(self.x).is_ok_and(|x| x < width)
+            && usize::try_from(self.y).is_ok_and(|y| y < height)
+    }
+
+    /// Orthogonal neighbours, clockwise starting north.
+    pub fn neighbours4(self) -> impl Iterator<Item = Point> {
+        Direction::ALL.into_iter().map(move |d| self.step(d))
+    }
+
+    /// Orthogonal and diagonal neighbours, clockwise starting north.
+    pub fn neighbours8(self) -> impl Iterator<Item = Point> {
+        Direction8::ALL.into_iter().map(move |d| self.step(d))
+    }
+
+    /// The grid position of the point, if both coordinates are non-negative.
+    #[must_use]
+    pub fn to_pos(self) -> Option<Pos> {
+        Some((usize::try_from(self.x).ok()?, usize::try_from(self.y).ok()?))
+    }
+}
+
+impl From<Pos> for Point {
+    fn from((x, y): Pos) -> Self {
+        Self::new(
+            i64::try_from(x).expect("x fits into i64"),
+            i64::try_from(y).expect("y fits into i64"),
+        )
+    }
+}
+
+impl Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl Vector {
+    #[must_use]
+    pub const fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    #[must_use]
+    pub fn manhattan(self) -> i64 {
+        self.x.abs() + self.y.abs()
+    }
+
+    /// Turn 90 degrees clockwise.
+    #[must_use]
+    pub fn rotate_right(self) -> Self {
+        Self::new(-self.y, self.x)
+    }
+
+    /// Turn 90 degrees counter-clockwise.
+    #[must_use]
+    pub fn rotate_left(self) -> Self {
+        Self::new(self.y, -self.x)
+    }
+}
+
+impl From<Direction> for Vector {
+    fn from(direction: Direction) -> Self {
+        direction.vector()
+    }
+}
+
+impl From<Direction8> for Vector {
+    fn from(direction: Direction8) -> Self {
+        direction.vector()
+    }
+}
+
+impl Add<Vector> for Point {
+    type Output = Point;
+
+    fn add(self, rhs: Vector) -> Point {
+        Point::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl AddAssign<Vector> for Point {
+    fn add_assign(&mut self, rhs: Vector) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub<Vector> for Point {
+    type Output = Point;
+
+    fn sub(self, rhs: Vector) -> Point {
+        Point::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl SubAssign<Vector> for Point {
+    fn sub_assign(&mut self, rhs: Vector) {
+        *self = *self - rhs;
+    }
+}
+
+impl Sub for Point {
+    type Output = Vector;
+
+    fn sub(self, rhs: Point) -> Vector {
+        Vector::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Add for Vector {
+    type Output = Vector;
+
+    fn add(self, rhs: Vector) -> Vector {
+        Vector::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for Vector {
+    type Output = Vector;
+
+    fn sub(self, rhs: Vector) -> Vector {
+        Vector::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Neg for Vector {
+    type Output = Vector;
+
+    fn neg(self) -> Vector {
+        Vector::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<i64> for Vector {
+    type Output = Vector;
+
+    fn mul(self, rhs: i64) -> Vector {
+        Vector::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{Direction, Direction8, Point, Vector};
+
+    #[test]
+    fn parses_arrows() {
+        for d in Direction::ALL {
+            assert_eq!(Direction::from_char(d.to_char()), Some(d));
+        }
+        assert_eq!(Direction::from_char('x'), None);
+        assert_eq!(Direction::West.to_string(), "<");
+    }
+
+    #[test]
+    fn rotates_directions() {
+        assert_eq!(Direction::North.rotate_right(), Direction::East);
+        assert_eq!(Direction::North.rotate_left(), Direction::West);
+        assert_eq!(Direction::East.opposite(), Direction::West);
+        assert_eq!(Direction8::NorthEast.rotate_right(), Direction8::East);
+        assert_eq!(Direction8::North.rotate_left(), Direction8::NorthWest);
+        assert_eq!(Direction8::SouthWest.opposite(), Direction8::NorthEast);
+        assert_eq!(Direction8::from(Direction::South), Direction8::South);
+        assert!(Direction8::SouthEast.is_diagonal());
+        for d in Direction::ALL {
+            assert_eq!(d.vector().rotate_right(), d.rotate_right().vector());
+            assert_eq!(d.vector().rotate_left(), d.rotate_left().vector());
+            assert_eq!(-d.vector(), d.opposite().vector());
+        }
+    }
+
+    #[test]
+    fn steps_points() {
+        let p = Point::new(0, 0);
+        assert_eq!(p.step(Direction::South), Point::new(0, 1));
+        assert_eq!(p.step(Direction8::NorthWest), Point::new(-1, -1));
+        assert_eq!(p.step_within(Direction::North, 3, 3), None);
+        assert_eq!(p.step_within(Direction::East, 3, 3), Some(Point::new(1, 0)));
+        assert_eq!(Point::new(2, 0).step_within(Direction::East, 3, 3), None);
+        assert_eq!(Direction::West.step((0, 4)), None);
+        assert_eq!(Direction::South.step((0, 4)), Some((0, 5)));
+        assert_eq!(p.neighbours4().count(), 4);
+        assert_eq!(p.neighbours8().filter(|n| n.manhattan(p) == 2).count(), 4);
+    }
+
+    #[test]
+    fn computes_with_vectors() {
+        let a = Point::new(1, 2);
+        let b = Point::new(4, -2);
+        assert_eq!(b - a, Vector::new(3, -4));
+        assert_eq!(a + (b - a) * 2, Point::new(7, -6));
+        assert_eq!(a.manhattan(b), 7);
+        assert_eq!(Point::from((3, 4)).to_pos(), Some((3, 4)));
+        assert_eq!(Point::new(-1, 4).to_pos(), None);
+    }
+}
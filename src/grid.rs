@@ -0,0 +1,561 @@
+/// A rectangular grid of cells, as found in most puzzle inputs.
+///
+/// Positions are `(x, y)` with the origin in the top left corner, `x` growing to the right and `y` growing down.
+use std::fmt::{self, Display};
+use std::ops::{Index, IndexMut};
+
+pub type Pos = (usize, usize);
+
+/// Offsets of the four orthogonal neighbours, clockwise starting north.
+pub const DELTAS4: [(i64, i64); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// Offsets of all eight neighbours, clockwise starting north.
+pub const DELTAS8: [(i64, i64); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum GridError {
+    Empty,
+    Ragged {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    InvalidChar {
+        pos: Pos,
+        ch: char,
+    },
+}
+
+impl Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridError::Empty => write!(f, "grid is empty"),
+            GridError::Ragged {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {line} has {found} cells, expected {expected} like the first line"
+            ),
+            GridError::InvalidChar { pos, ch } => {
+                write!(f, "unexpected character '{ch}' at {pos:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Parse a grid with one cell per character. Returns an error if `mapper` returns `None` for a character
+    /// or if lines differ in length. A trailing empty line is ignored.
+    pub fn parse(
+        input: &str,
+        mut mapper: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self, GridError> {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = vec![];
+
+        for (y, line) in input.trim_end_matches(['\n', '\r']).lines().enumerate() {
+            let len_before = cells.len();
+            for (x, ch) in line.chars().enumerate() {
+                cells.push(mapper(ch).ok_or(GridError::InvalidChar { pos: (x, y), ch })?);
+            }
+            let found = cells.len() - len_before;
+            match width {
+                None => width = Some(found),
+                Some(expected) if expected != found => {
+                    return Err(GridError::Ragged {
+                        line: y + 1,
+                        expected,
+                        found,
+                    })
+                }
+                Some(_) => {}
+            }
+            height += 1;
+        }
+
+        match width {
+            Some(width) if width > 0 => Ok(Self {
+                width,
+                height,
+                cells,
+            }),
+            _ => Err(GridError::Empty),
+        }
+    }
+
+    /// Create a grid from cells in row-major order.
+    ///
+    /// # Panics
+    /// If the number of cells is not a multiple of `width`.
+    #[must_use]
+    pub fn from_vec(width: usize, cells: Vec<T>) -> Self {
+        assert!(
+            width > 0 && cells.len().is_multiple_of(width),
+            "{} cells can't be split into rows of {width}",
+            cells.len()
+        );
+        Self {
+            width,
+            height: cells.len() / width,
+            cells,
+        }
+    }
+
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    #[must_use]
+    pub fn contains(&self, (x, y): Pos) -> bool {
+        x < self.width && y < self.height
+    }
+
+    #[must_use]
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.contains(pos)
+            .then(|| &self.cells[pos.1 * self.width + pos.0])
+    }
+
+    #[must_use]
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        self.contains(pos)
+            .then(|| &mut self.cells[pos.1 * self.width + pos.0])
+    }
+
+    /// Set a cell. Returns `false` if the position is out of bounds.
+    pub fn set(&mut self, pos: Pos, value: T) -> bool {
+        match self.get_mut(pos) {
+            Some(cell) => {
+                *cell = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Move from `pos` by `delta`. Returns `None` if the result is outside of the grid.
+    #[must_use]
+    pub fn step(&self, (x, y): Pos, (dx, dy): (i64, i64)) -> Option<Pos> {
+        let x = usize::try_from(i64::try_from(x).ok()? + dx).ok()?;
+        let y = usize::try_from(i64::try_from(y).ok()? + dy).ok()?;
+        self.contains((x, y)).then_some((x, y))
+    }
+
+    /// Orthogonal neighbours of `pos` that are inside the grid.
+    pub fn neighbours4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        DELTAS4.iter().filter_map(move |d| self.step(pos, *d))
+    }
+
+    /// Orthogonal and diagonal neighbours of `pos` that are inside the grid.
+    pub fn neighbours8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        DELTAS8.iter().filter_map(move |d| self.step(pos, *d))
+    }
+
+    /// Positions and cells from `start` (inclusive) in steps of `delta`, until the edge of the grid.
+    pub fn ray(&self, start: Pos, delta: (i64, i64)) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        std::iter::successors(self.contains(start).then_some(start), move |pos| {
+            self.step(*pos, delta)
+        })
+        .map(|pos| (pos, &self[pos]))
+    }
+
+    /// Cells of row `y`, left to right.
+    ///
+    /// # Panics
+    /// If `y` is out of bounds.
+    pub fn row(&self, y: usize) -> impl Iterator<Item = &T> + '_ {
+        self.cells[y * self.width..(y + 1) * self.width].iter()
+    }
+
+    /// Cells of column `x`, top to bottom.
+    ///
+    /// # Panics
+    /// If `x` is out of bounds.
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> + '_ {
+        assert!(x < self.width, "column {x} is out of bounds");
+        self.cells[x..].iter().step_by(self.width)
+    }
+
+    /// Cells on the diagonal through `pos`, from the top left to the bottom right. Empty if `pos` is out of
+    /// bounds.
+    pub fn diagonal(&self, pos: Pos) -> impl Iterator<Item = &T> + '_ {
+        let start = self
+            .contains(pos)
+            .then(|| pos.0.min(pos.1))
+            .map(|back| (pos.0 - back, pos.1 - back));
+        start
+            .into_iter()
+            .flat_map(|start| self.ray(start, (1, 1)).map(|(_, cell)| cell))
+    }
+
+    /// Cells on the anti-diagonal through `pos`, from the top right to the bottom left. Empty if `pos` is out of
+    /// bounds.
+    pub fn anti_diagonal(&self, pos: Pos) -> impl Iterator<Item = &T> + '_ {
+        let start = self
+            .contains(pos)
+            .then(|| (self.width - 1 - pos.0).min(pos.1))
+            .map(|back| (pos.0 + back, pos.1 - back));
+        start
+            .into_iter()
+            .flat_map(|start| self.ray(start, (-1, 1)).map(|(_, cell)| cell))
+    }
+
+    /// All positions in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.cells.len()).map(move |i| (i % width, i / width))
+    }
+
+    /// All positions and cells in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// Position of the first cell (in row-major order) that satisfies `predicate`.
+    pub fn position(&self, mut predicate: impl FnMut(&T) -> bool) -> Option<Pos> {
+        self.iter().find(|(_, c)| predicate(c)).map(|(pos, _)| pos)
+    }
+
+    /// Apply `f` to every cell.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    #[must_use]
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+}
+
+impl<T: PartialEq> Grid<T> {
+    /// Position of the first cell (in row-major order) that equals `value`.
+    pub fn find(&self, value: &T) -> Option<Pos> {
+        self.position(|c| c == value)
+    }
+
+    /// Positions of all cells that equal `value`.
+    pub fn find_all<'a>(&'a self, value: &'a T) -> impl Iterator<Item = Pos> + 'a {
+        self.iter()
+            .filter(move |(_, c)| *c == value)
+            .map(|(pos, _)| pos)
+    }
+}
+
+impl Grid<char> {
+    /// Parse a grid that keeps the characters as they are.
+    pub fn parse_chars(input: &str) -> Result<Self, GridError> {
+        Self::parse(input, Some)
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{pos:?} is out of bounds"))
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{pos:?} is out of bounds"))
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.cells.chunks(self.width) {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A set of grid positions, optionally with a small number of layers per position, e.g. one per direction to
+/// remember states like `(pos, direction as usize)`.
+///
+/// Positions are stored as bits. Clearing is `O(1)`: every word of bits carries the generation it was written
+/// in, and words from older generations count as empty. This makes it cheap to reuse in hot loops.
+#[derive(Clone, Debug)]
+pub struct VisitedSet {
+    width: usize,
+    height: usize,
+    layers: usize,
+    words: Vec<u64>,
+    generations: Vec<u32>,
+    generation: u32,
+    len: usize,
+}
+
+impl VisitedSet {
+    #[must_use]
+    pub fn new(width: usize, height: usize) -> Self {
+        Self::with_layers(width, height, 1)
+    }
+
+    /// A set of `(pos, layer)` pairs with `layer` in `0..layers`.
+    #[must_use]
+    pub fn with_layers(width: usize, height: usize, layers: usize) -> Self {
+        let words = (width * height * layers).div_ceil(64);
+        Self {
+            width,
+            height,
+            layers,
+            words: vec![0; words],
+            generations: vec![0; words],
+            generation: 0,
+            len: 0,
+        }
+    }
+
+    /// A set with the dimensions of `grid`.
+    #[must_use]
+    pub fn for_grid<T>(grid: &Grid<T>, layers: usize) -> Self {
+        Self::with_layers(grid.width(), grid.height(), layers)
+    }
+
+    fn bit(&self, (x, y): Pos, layer: usize) -> Option<(usize, u64)> {
+        if x >= self.width || y >= self.height || layer >= self.layers {
+            return None;
+        }
+        let index = (y * self.width + x) * self.layers + layer;
+        Some((index / 64, 1 << (index % 64)))
+    }
+
+    fn word(&self, word: usize) -> u64 {
+        if self.generations[word] == self.generation {
+            self.words[word]
+        } else {
+            0
+        }
+    }
+
+    /// Add `pos`. Returns `false` if it was already in the set or is out of bounds.
+    pub fn insert(&mut self, pos: Pos) -> bool {
+        self.insert_layer(pos, 0)
+    }
+
+    /// Add `(pos, layer)`. Returns `false` if it was already in the set or is out of bounds.
+    pub fn insert_layer(&mut self, pos: Pos, layer: usize) -> bool {
+        let Some((word, mask)) = self.bit(pos, layer) else {
+            return false;
+        };
+        let bits = self.word(word);
+        if bits & mask != 0 {
+            return false;
+        }
+        self.words[word] = bits | mask;
+        self.generations[word] = self.generation;
+        self.len += 1;
+        true
+    }
+
+    #[must_use]
+    pub fn contains(&self, pos: Pos) -> bool {
+        self.contains_layer(pos, 0)
+    }
+
+    #[must_use]
+    pub fn contains_layer(&self, pos: Pos, layer: usize) -> bool {
+        self.bit(pos, layer)
+            .is_some_and(|(word, mask)| self.word(word) & mask != 0)
+    }
+
+    /// Whether `pos` is in the set in any layer.
+    #[must_use]
+    pub fn contains_any(&self, pos: Pos) -> bool {
+        (0..self.layers).any(|layer| self.contains_layer(pos, layer))
+    }
+
+    /// Number of `(pos, layer)` pairs in the set.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove everything without touching the bits.
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.generation = self.generation.wrapping_add(1);
+        if self.generation == 0 {
+            // Words from 2^32 generations ago would look current again.
+            self.words.fill(0);
+            self.generations.fill(0);
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{Grid, GridError, VisitedSet};
+
+    const INPUT: &str = "#.S\n.#.\nE..\n";
+
+    #[test]
+    fn parses_and_displays() {
+        let grid = Grid::parse_chars(INPUT).unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 3));
+        assert_eq!(grid[(2, 0)], 'S');
+        assert_eq!(grid.to_string(), INPUT);
+    }
+
+    #[test]
+    fn parses_with_mapper() {
+        let grid = Grid::parse("01\n23", |c| c.to_digit(10)).unwrap();
+        assert_eq!(grid.get((1, 1)), Some(&3));
+        assert_eq!(grid.get((2, 1)), None);
+        assert_eq!(
+            Grid::parse("01\n2x", |c| c.to_digit(10)),
+            Err(GridError::InvalidChar {
+                pos: (1, 1),
+                ch: 'x'
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_bad_shapes() {
+        assert_eq!(
+            Grid::parse_chars("..\n...\n"),
+            Err(GridError::Ragged {
+                line: 2,
+                expected: 2,
+                found: 3
+            })
+        );
+        assert_eq!(Grid::parse_chars(""), Err(GridError::Empty));
+    }
+
+    #[test]
+    fn finds_positions() {
+        let grid = Grid::parse_chars(INPUT).unwrap();
+        assert_eq!(grid.find(&'S'), Some((2, 0)));
+        assert_eq!(grid.find(&'E'), Some((0, 2)));
+        assert_eq!(grid.find(&'x'), None);
+        assert_eq!(grid.find_all(&'#').collect::<Vec<_>>(), [(0, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn checks_neighbours() {
+        let grid = Grid::parse_chars(INPUT).unwrap();
+        assert_eq!(
+            grid.neighbours4((0, 0)).collect::<Vec<_>>(),
+            [(1, 0), (0, 1)]
+        );
+        assert_eq!(grid.neighbours4((1, 1)).count(), 4);
+        assert_eq!(
+            grid.neighbours8((2, 2)).collect::<Vec<_>>(),
+            [(2, 1), (1, 2), (1, 1)]
+        );
+        assert_eq!(grid.step((0, 0), (-1, 0)), None);
+        assert_eq!(grid.step((0, 0), (2, 2)), Some((2, 2)));
+    }
+
+    #[test]
+    fn iterates_lines() {
+        let grid = Grid::parse_chars("abc\ndef\nghi").unwrap();
+        let collect = |it: &mut dyn Iterator<Item = &char>| it.collect::<String>();
+        assert_eq!(collect(&mut grid.row(1)), "def");
+        assert_eq!(collect(&mut grid.column(2)), "cfi");
+        assert_eq!(collect(&mut grid.diagonal((2, 1))), "bf");
+        assert_eq!(collect(&mut grid.diagonal((1, 1))), "aei");
+        assert_eq!(collect(&mut grid.anti_diagonal((1, 1))), "ceg");
+        assert_eq!(collect(&mut grid.anti_diagonal((0, 0))), "a");
+        assert_eq!(collect(&mut grid.diagonal((3, 3))), "");
+        assert_eq!(collect(&mut grid.anti_diagonal((3, 0))), "");
+        assert_eq!(collect(&mut grid.anti_diagonal((0, 3))), "");
+        assert_eq!(
+            grid.ray((0, 2), (1, -1))
+                .map(|(_, c)| *c)
+                .collect::<String>(),
+            "gec"
+        );
+    }
+
+    #[test]
+    fn maps_and_sets() {
+        let mut grid = Grid::new(2, 2, 0);
+        assert!(grid.set((1, 0), 5));
+        assert!(!grid.set((2, 0), 5));
+        grid[(0, 1)] = 2;
+        let doubled = grid.map(|v| v * 2);
+        assert_eq!(doubled.to_string(), "010\n40\n");
+        assert_eq!(Grid::from_vec(2, vec![1, 2, 3, 4]).row(1).sum::<i32>(), 7);
+    }
+
+    #[test]
+    fn tracks_visited_positions() {
+        let mut visited = VisitedSet::with_layers(9, 9, 4);
+        assert!(visited.insert_layer((8, 8), 3));
+        assert!(!visited.insert_layer((8, 8), 3));
+        assert!(visited.insert_layer((8, 8), 0));
+        assert!(!visited.insert_layer((9, 0), 0));
+        assert!(!visited.insert_layer((0, 0), 4));
+        assert!(visited.contains_layer((8, 8), 3));
+        assert!(!visited.contains_layer((8, 8), 1));
+        assert!(visited.contains_any((8, 8)));
+        assert_eq!(visited.len(), 2);
+
+        visited.clear();
+        assert!(visited.is_empty());
+        assert!(!visited.contains_layer((8, 8), 3));
+        assert!(visited.insert_layer((8, 8), 3));
+        assert!(!visited.contains_layer((8, 8), 0));
+        assert_eq!(visited.len(), 1);
+
+        let grid = Grid::parse_chars(INPUT).unwrap();
+        let mut visited = VisitedSet::for_grid(&grid, 1);
+        assert!(visited.insert((2, 2)));
+        assert!(visited.contains((2, 2)));
+        assert!(!visited.contains((2, 1)));
+    }
+}
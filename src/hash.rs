@@ -0,0 +1,132 @@
+/// A fast, non-cryptographic hasher for the small integer and tuple keys of most puzzles.
+///
+/// This is the FxHash function used by rustc. It is not resistant to hash flooding, which does not matter for
+/// puzzle inputs. Build with the `std-hash` feature to make [`FastMap`] and [`FastSet`] use the standard
+/// library's SipHash instead, e.g. to compare both with `cargo time --compare-hash`.
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasherDefault, Hasher};
+
+const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FxHasher {
+    hash: u64,
+}
+
+impl FxHasher {
+    #[inline]
+    fn add(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(SEED);
+    }
+}
+
+impl Hasher for FxHasher {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        let mut chunks = bytes.chunks_exact(8);
+        for chunk in &mut chunks {
+            self.add(u64::from_le_bytes(chunk.try_into().unwrap()));
+        }
+        let rest = chunks.remainder();
+        if !rest.is_empty() {
+            let mut word = [0; 8];
+            word[..rest.len()].copy_from_slice(rest);
+            self.add(u64::from_le_bytes(word));
+        }
+    }
+
+    #[inline]
+    fn write_u8(&mut self, i: u8) {
+        self.add(u64::from(i));
+    }
+
+    #[inline]
+    fn write_u16(&mut self, i: u16) {
+        self.add(u64::from(i));
+    }
+
+    #[inline]
+    fn write_u32(&mut self, i: u32) {
+        self.add(u64::from(i));
+    }
+
+    #[inline]
+    fn write_u64(&mut self, i: u64) {
+        self.add(i);
+    }
+
+    #[inline]
+    fn write_u128(&mut self, i: u128) {
+        self.add(i as u64);
+        self.add((i >> 64) as u64);
+    }
+
+    #[inline]
+    fn write_usize(&mut self, i: usize) {
+        self.add(i as u64);
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+pub type FxBuildHasher = BuildHasherDefault<FxHasher>;
+
+/// The hasher used by [`FastMap`] and [`FastSet`].
+#[cfg(not(feature = "std-hash"))]
+pub type FastState = FxBuildHasher;
+#[cfg(feature = "std-hash")]
+pub type FastState = std::collections::hash_map::RandomState;
+
+/// A `HashMap` with a fast hasher. Create it with `FastMap::default()`.
+pub type FastMap<K, V> = HashMap<K, V, FastState>;
+
+/// A `HashSet` with a fast hasher. Create it with `FastSet::default()`.
+pub type FastSet<T> = HashSet<T, FastState>;
+
+/// A [`FastMap`] with room for at least `capacity` entries.
+#[must_use]
+pub fn map_with_capacity<K, V>(capacity: usize) -> FastMap<K, V> {
+    FastMap::with_capacity_and_hasher(capacity, FastState::default())
+}
+
+/// A [`FastSet`] with room for at least `capacity` values.
+#[must_use]
+pub fn set_with_capacity<T>(capacity: usize) -> FastSet<T> {
+    FastSet::with_capacity_and_hasher(capacity, FastState::default())
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{map_with_capacity, FastMap, FastSet, FxHasher};
+    use std::hash::{Hash, Hasher};
+
+    fn hash(value: impl Hash) -> u64 {
+        let mut hasher = FxHasher::default();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn hashes_deterministically() {
+        assert_eq!(hash((1_u32, 2_u32)), hash((1_u32, 2_u32)));
+        assert_ne!(hash((1_u32, 2_u32)), hash((2_u32, 1_u32)));
+        assert_ne!(hash("abcdefghi"), hash("abcdefghj"));
+        assert_ne!(hash(0_u64), hash(1_u64));
+    }
+
+    #[test]
+    fn works_as_map_and_set() {
+        let mut map: FastMap<(usize, usize), u32> = map_with_capacity(4);
+        *map.entry((1, 2)).or_default() += 3;
+        *map.entry((1, 2)).or_default() += 4;
+        assert_eq!(map[&(1, 2)], 7);
+
+        let set: FastSet<i64> = (0..1000).map(|i| i % 10).collect();
+        assert_eq!(set.len(), 10);
+    }
+}
@@ -1,3 +1,17 @@
 pub mod template;
 
 // Use this file to add helper functions and additional modules.
+pub mod cycle;
+pub mod geometry;
+pub mod grid;
+pub mod hash;
+pub mod math;
+pub mod memo;
+pub mod order;
+pub mod par;
+pub mod parse;
+pub mod ranges;
+pub mod regions;
+pub mod search;
+pub mod viz;
+pub mod vm;
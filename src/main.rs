@@ -1,4 +1,6 @@
-use advent_of_code::template::commands::{all, download, read, scaffold, solve, time};
+use advent_of_code::template::commands::{
+    all, download, leaderboard, progress, read, scaffold, solve, time,
+};
 use args::{parse, AppArguments};
 
 #[cfg(feature = "today")]
@@ -7,6 +9,7 @@ use advent_of_code::template::Day;
 use std::process;
 
 mod args {
+    use advent_of_code::template::commands::solve::VizOptions;
     use advent_of_code::template::Day;
     use std::process;
 
@@ -26,6 +29,9 @@ mod args {
             day: Day,
             release: bool,
             dhat: bool,
+            verbose: bool,
+            viz: VizOptions,
+            threads: Option<usize>,
             submit: Option<u8>,
         },
         All {
@@ -35,6 +41,12 @@ mod args {
             all: bool,
             day: Option<Day>,
             store: bool,
+            compare_hash: bool,
+            threads: Option<usize>,
+        },
+        Progress,
+        Leaderboard {
+            source: String,
         },
         #[cfg(feature = "today")]
         Today,
@@ -50,13 +62,21 @@ mod args {
             Some("time") => {
                 let all = args.contains("--all");
                 let store = args.contains("--store");
+                let compare_hash = args.contains("--compare-hash");
+                let threads = args.opt_value_from_str("--threads")?;
 
                 AppArguments::Time {
                     all,
                     day: args.opt_free_from_str()?,
                     store,
+                    compare_hash,
+                    threads,
                 }
             }
+            Some("progress") => AppArguments::Progress,
+            Some("leaderboard") => AppArguments::Leaderboard {
+                source: args.free_from_str()?,
+            },
             Some("download") => AppArguments::Download {
                 day: args.free_from_str()?,
             },
@@ -73,6 +93,14 @@ mod args {
                 release: args.contains("--release"),
                 submit: args.opt_value_from_str("--submit")?,
                 dhat: args.contains("--dhat"),
+                verbose: args.contains("--verbose"),
+                viz: VizOptions {
+                    terminal: args.contains("--viz"),
+                    fps: args.opt_value_from_str("--viz-fps")?,
+                    out: args.opt_value_from_str("--viz-out")?,
+                    format: args.opt_value_from_str("--viz-format")?,
+                },
+                threads: args.opt_value_from_str("--threads")?,
             },
             #[cfg(feature = "today")]
             Some("today") => AppArguments::Today,
@@ -103,7 +131,15 @@ fn main() {
         }
         Ok(args) => match args {
             AppArguments::All { release } => all::handle(release),
-            AppArguments::Time { day, all, store } => time::handle(day, all, store),
+            AppArguments::Time {
+                day,
+                all,
+                store,
+                compare_hash,
+                threads,
+            } => time::handle(day, all, store, compare_hash, threads),
+            AppArguments::Progress => progress::handle(),
+            AppArguments::Leaderboard { source } => leaderboard::handle(&source),
             AppArguments::Download { day } => download::handle(day),
             AppArguments::Read { day } => read::handle(day),
             AppArguments::Scaffold {
@@ -120,8 +156,11 @@ fn main() {
                 day,
                 release,
                 dhat,
+                verbose,
+                viz,
+                threads,
                 submit,
-            } => solve::handle(day, release, dhat, submit),
+            } => solve::handle(day, release, dhat, verbose, &viz, threads, submit),
             #[cfg(feature = "today")]
             AppArguments::Today => {
                 match Day::today() {
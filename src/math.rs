@@ -0,0 +1,473 @@
+/// Exact integer math: gcd and lcm, modular arithmetic, the Chinese remainder theorem and linear systems.
+///
+/// Number theory functions work on `i64` and `i128`. Functions that can overflow either have a `checked_`
+/// variant or return [`MathError::Overflow`] instead of panicking.
+use std::fmt::{self, Debug, Display};
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MathError {
+    /// An intermediate result does not fit into the integer type.
+    Overflow,
+    /// The system or congruences have no solution.
+    NoSolution,
+    /// The system has no unique solution, e.g. the determinant is zero.
+    Singular,
+    /// The unique solution is not integral.
+    NotInteger,
+}
+
+impl Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MathError::Overflow => write!(f, "integer overflow"),
+            MathError::NoSolution => write!(f, "no solution"),
+            MathError::Singular => write!(f, "no unique solution"),
+            MathError::NotInteger => write!(f, "solution is not integral"),
+        }
+    }
+}
+
+impl std::error::Error for MathError {}
+
+/// Signed integer types supported by this module.
+pub trait Int:
+    Copy
+    + Ord
+    + Debug
+    + Display
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+    + Neg<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn checked_neg(self) -> Option<Self>;
+    fn rem_euclid(self, rhs: Self) -> Self;
+}
+
+macro_rules! impl_int {
+    ($($t:ty),*) => {$(
+        impl Int for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_sub(self, rhs)
+            }
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_mul(self, rhs)
+            }
+            fn checked_neg(self) -> Option<Self> {
+                <$t>::checked_neg(self)
+            }
+            fn rem_euclid(self, rhs: Self) -> Self {
+                <$t>::rem_euclid(self, rhs)
+            }
+        }
+    )*};
+}
+
+impl_int!(i64, i128);
+
+fn abs<T: Int>(value: T) -> T {
+    if value < T::ZERO {
+        -value
+    } else {
+        value
+    }
+}
+
+/// Greatest common divisor, always non-negative. `gcd(0, 0)` is 0.
+pub fn gcd<T: Int>(a: T, b: T) -> T {
+    let (mut a, mut b) = (abs(a), abs(b));
+    while b != T::ZERO {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Least common multiple, always non-negative.
+///
+/// # Panics
+/// If the result overflows, see [`checked_lcm`].
+pub fn lcm<T: Int>(a: T, b: T) -> T {
+    checked_lcm(a, b).expect("lcm overflowed")
+}
+
+pub fn checked_lcm<T: Int>(a: T, b: T) -> Option<T> {
+    if a == T::ZERO || b == T::ZERO {
+        return Some(T::ZERO);
+    }
+    abs(a / gcd(a, b)).checked_mul(abs(b))
+}
+
+/// Least common multiple of all values, e.g. the period of several cycles.
+pub fn lcm_all<T: Int>(values: impl IntoIterator<Item = T>) -> Option<T> {
+    values.into_iter().try_fold(T::ONE, checked_lcm)
+}
+
+/// Extended Euclid: returns `(g, x, y)` with `a * x + b * y = g = gcd(a, b)`.
+pub fn egcd<T: Int>(a: T, b: T) -> (T, T, T) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (T::ONE, T::ZERO);
+    let (mut old_y, mut y) = (T::ZERO, T::ONE);
+    while r != T::ZERO {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < T::ZERO {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// `a * b mod m` in `0..m`. Falls back to double-and-add if the product overflows.
+fn checked_mul_mod<T: Int>(a: T, b: T, m: T) -> Option<T> {
+    let (a, b) = (a.rem_euclid(m), b.rem_euclid(m));
+    if let Some(product) = a.checked_mul(b) {
+        return Some(product % m);
+    }
+    let (mut result, mut a, mut b) = (T::ZERO, a, b);
+    let two = T::ONE + T::ONE;
+    while b > T::ZERO {
+        if b % two == T::ONE {
+            result = result.checked_add(a)? % m;
+        }
+        a = a.checked_add(a)? % m;
+        b = b / two;
+    }
+    Some(result)
+}
+
+/// `base ^ exp mod m` in `0..m`.
+///
+/// # Panics
+/// If `m` is not positive, `exp` is negative or an intermediate result overflows, see [`checked_mod_pow`].
+pub fn mod_pow<T: Int>(base: T, exp: T, m: T) -> T {
+    assert!(m > T::ZERO, "modulus {m} is not positive");
+    assert!(exp >= T::ZERO, "exponent {exp} is negative");
+    checked_mod_pow(base, exp, m).expect("mod_pow overflowed")
+}
+
+/// `base ^ exp mod m` in `0..m`, or `None` if `m` is not positive, `exp` is negative or the computation overflows.
+pub fn checked_mod_pow<T: Int>(base: T, exp: T, m: T) -> Option<T> {
+    if m <= T::ZERO || exp < T::ZERO {
+        return None;
+    }
+    let two = T::ONE + T::ONE;
+    let (mut result, mut base, mut exp) = (T::ONE % m, base.rem_euclid(m), exp);
+    while exp > T::ZERO {
+        if exp % two == T::ONE {
+            result = checked_mul_mod(result, base, m)?;
+        }
+        base = checked_mul_mod(base, base, m)?;
+        exp = exp / two;
+    }
+    Some(result)
+}
+
+/// The inverse of `a` modulo `m` in `0..m`, or `None` if `a` and `m` are not coprime.
+pub fn mod_inv<T: Int>(a: T, m: T) -> Option<T> {
+    if m <= T::ZERO {
+        return None;
+    }
+    let (g, x, _) = egcd(a.rem_euclid(m), m);
+    (g == T::ONE).then(|| x.rem_euclid(m))
+}
+
+/// Solve `x ≡ r (mod m)` for all `(r, m)` pairs. Returns `(x, lcm of all m)` with `x` in `0..lcm`.
+/// Moduli do not have to be coprime.
+pub fn crt<T: Int>(congruences: impl IntoIterator<Item = (T, T)>) -> Result<(T, T), MathError> {
+    let (mut x, mut m) = (T::ZERO, T::ONE);
+    for (r, n) in congruences {
+        if n <= T::ZERO {
+            return Err(MathError::NoSolution);
+        }
+        let (g, p, _) = egcd(m, n);
+        let diff = r.checked_sub(x).ok_or(MathError::Overflow)?;
+        if diff % g != T::ZERO {
+            return Err(MathError::NoSolution);
+        }
+        // x + m * k ≡ r (mod n)  =>  k ≡ (diff / g) * p (mod n / g)
+        let n_g = n / g;
+        let k = checked_mul_mod(diff / g, p, n_g).ok_or(MathError::Overflow)?;
+        let next_m = m.checked_mul(n_g).ok_or(MathError::Overflow)?;
+        x = x
+            .checked_add(checked_mul_mod(m, k, next_m).ok_or(MathError::Overflow)?)
+            .ok_or(MathError::Overflow)?
+            .rem_euclid(next_m);
+        m = next_m;
+    }
+    Ok((x, m))
+}
+
+/// Solve `a * [x, y] = b` exactly for a unique integer solution, e.g. `[[ax, bx], [ay, by]] * [a, b] = [px, py]`.
+pub fn solve_2x2<T: Int>(a: [[T; 2]; 2], b: [T; 2]) -> Result<[T; 2], MathError> {
+    let mul = |x: T, y: T| x.checked_mul(y).ok_or(MathError::Overflow);
+    let sub = |x: T, y: T| x.checked_sub(y).ok_or(MathError::Overflow);
+
+    let det = sub(mul(a[0][0], a[1][1])?, mul(a[0][1], a[1][0])?)?;
+    if det == T::ZERO {
+        return Err(MathError::Singular);
+    }
+    // Cramer's rule.
+    let x = sub(mul(b[0], a[1][1])?, mul(a[0][1], b[1])?)?;
+    let y = sub(mul(a[0][0], b[1])?, mul(b[0], a[1][0])?)?;
+    if x % det != T::ZERO || y % det != T::ZERO {
+        return Err(MathError::NotInteger);
+    }
+    Ok([x / det, y / det])
+}
+
+/// An exact fraction, always normalized with a positive denominator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational { num: 0, den: 1 };
+
+    /// Returns `None` if `den` is zero.
+    #[must_use]
+    pub fn new(num: i128, den: i128) -> Option<Self> {
+        if den == 0 {
+            return None;
+        }
+        let g = gcd(num, den);
+        let sign = if den < 0 { -1 } else { 1 };
+        Some(Self {
+            num: sign * num / g,
+            den: sign * den / g,
+        })
+    }
+
+    #[must_use]
+    pub fn integer(value: i128) -> Self {
+        Self { num: value, den: 1 }
+    }
+
+    #[must_use]
+    pub fn numerator(self) -> i128 {
+        self.num
+    }
+
+    #[must_use]
+    pub fn denominator(self) -> i128 {
+        self.den
+    }
+
+    #[must_use]
+    pub fn is_integer(self) -> bool {
+        self.den == 1
+    }
+
+    #[must_use]
+    pub fn to_integer(self) -> Option<i128> {
+        self.is_integer().then_some(self.num)
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        let den = checked_lcm(self.den, rhs.den)?;
+        let lhs_num = self.num.checked_mul(den / self.den)?;
+        let rhs_num = rhs.num.checked_mul(den / rhs.den)?;
+        Self::new(lhs_num.checked_add(rhs_num)?, den)
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.checked_add(Self::new(rhs.num.checked_neg()?, rhs.den)?)
+    }
+
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        // cross-reduce first to keep the intermediate values small.
+        let g1 = gcd(self.num, rhs.den).max(1);
+        let g2 = gcd(rhs.num, self.den).max(1);
+        Self::new(
+            (self.num / g1).checked_mul(rhs.num / g2)?,
+            (self.den / g2).checked_mul(rhs.den / g1)?,
+        )
+    }
+
+    /// Returns `None` on overflow or division by zero.
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        self.checked_mul(Self::new(rhs.den, rhs.num)?)
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+/// Solve the square system `a * x = b` exactly with Gaussian elimination over fractions.
+pub fn solve_linear(a: &[Vec<i128>], b: &[i128]) -> Result<Vec<Rational>, MathError> {
+    let n = b.len();
+    assert!(
+        a.len() == n && a.iter().all(|row| row.len() == n),
+        "expected a {n}x{n} matrix"
+    );
+
+    let overflow = MathError::Overflow;
+    let mut m: Vec<Vec<Rational>> = a
+        .iter()
+        .zip(b)
+        .map(|(row, b)| {
+            row.iter()
+                .chain([b])
+                .map(|v| Rational::integer(*v))
+                .collect()
+        })
+        .collect();
+
+    for col in 0..n {
+        let pivot = (col..n)
+            .find(|&row| m[row][col] != Rational::ZERO)
+            .ok_or(MathError::Singular)?;
+        m.swap(col, pivot);
+
+        let pivot_row = m[col].clone();
+        for (i, row) in m.iter_mut().enumerate() {
+            if i == col || row[col] == Rational::ZERO {
+                continue;
+            }
+            let factor = row[col].checked_div(pivot_row[col]).ok_or(overflow)?;
+            for (value, pivot_value) in row.iter_mut().zip(&pivot_row).skip(col) {
+                let delta = pivot_value.checked_mul(factor).ok_or(overflow)?;
+                *value = value.checked_sub(delta).ok_or(overflow)?;
+            }
+        }
+    }
+
+    m.iter()
+        .enumerate()
+        .map(|(i, row)| row[n].checked_div(row[i]).ok_or(overflow))
+        .collect()
+}
+
+/// Solve the square system `a * x = b` for a unique integer solution.
+pub fn solve_linear_int(a: &[Vec<i128>], b: &[i128]) -> Result<Vec<i128>, MathError> {
+    solve_linear(a, b)?
+        .into_iter()
+        .map(|x| x.to_integer().ok_or(MathError::NotInteger))
+        .collect()
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        checked_lcm, checked_mod_pow, crt, egcd, gcd, lcm, lcm_all, mod_inv, mod_pow, solve_2x2,
+        solve_linear, solve_linear_int, MathError, Rational,
+    };
+
+    #[test]
+    fn computes_gcd_and_lcm() {
+        assert_eq!(gcd(12_i64, -18), 6);
+        assert_eq!(gcd(0_i64, 0), 0);
+        assert_eq!(lcm(4_i64, 6), 12);
+        assert_eq!(lcm(-4_i128, 6), 12);
+        assert_eq!(checked_lcm(i64::MAX, i64::MAX - 1), None);
+        assert_eq!(lcm_all([2_i64, 3, 4, 5]), Some(60));
+        let (g, x, y) = egcd(240_i64, 46);
+        assert_eq!(g, 2);
+        assert_eq!(240 * x + 46 * y, 2);
+    }
+
+    #[test]
+    fn computes_modular_arithmetic() {
+        assert_eq!(mod_pow(4_i64, 13, 497), 445);
+        assert_eq!(mod_pow(-2_i64, 3, 5), 2);
+        assert_eq!(mod_pow(7_i64, 0, 1), 0);
+        // the product of two residues does not fit into an i128.
+        let m = (1_i128 << 120) + 1;
+        assert_eq!(mod_pow(2_i128, 240, m), 1);
+        assert_eq!(checked_mod_pow(2_i64, -1, 5), None);
+        assert_eq!(mod_inv(3_i64, 11), Some(4));
+        assert_eq!(mod_inv(-3_i64, 11), Some(7));
+        assert_eq!(mod_inv(4_i64, 8), None);
+    }
+
+    #[test]
+    fn solves_congruences() {
+        assert_eq!(crt([(2_i64, 3), (3, 5), (2, 7)]), Ok((23, 105)));
+        assert_eq!(crt([(1_i64, 4), (3, 6)]), Ok((9, 12)));
+        assert_eq!(crt([(1_i64, 4), (2, 6)]), Err(MathError::NoSolution));
+        assert_eq!(crt::<i64>([]), Ok((0, 1)));
+        let big = i64::MAX / 2;
+        assert_eq!(crt([(0_i64, big), (1, big - 2)]), Err(MathError::Overflow));
+    }
+
+    #[test]
+    fn solves_2x2_systems() {
+        // Button A: X+94, Y+34; Button B: X+22, Y+67; Prize: X=8400, Y=5400
+        assert_eq!(
+            solve_2x2([[94_i64, 22], [34, 67]], [8400, 5400]),
+            Ok([80, 40])
+        );
+        assert_eq!(
+            solve_2x2([[26_i64, 67], [66, 21]], [12748, 12176]),
+            Err(MathError::NotInteger)
+        );
+        assert_eq!(
+            solve_2x2([[1_i64, 2], [2, 4]], [3, 6]),
+            Err(MathError::Singular)
+        );
+        let offset = 10_000_000_000_000_i64;
+        assert_eq!(
+            solve_2x2([[26_i64, 67], [66, 21]], [12748 + offset, 12176 + offset]),
+            Ok([118_679_050_709, 103_199_174_542])
+        );
+        assert_eq!(
+            solve_2x2([[i64::MAX, 1], [1, i64::MAX]], [1, 1]),
+            Err(MathError::Overflow)
+        );
+    }
+
+    #[test]
+    fn solves_linear_systems() {
+        let a = vec![vec![2, 1, -1], vec![-3, -1, 2], vec![-2, 1, 2]];
+        assert_eq!(solve_linear_int(&a, &[8, -11, -3]), Ok(vec![2, 3, -1]));
+
+        let a = vec![vec![2, 0], vec![0, 3]];
+        let x = solve_linear(&a, &[1, 1]).unwrap();
+        assert_eq!(
+            x,
+            [Rational::new(1, 2).unwrap(), Rational::new(1, 3).unwrap()]
+        );
+        assert_eq!(x[0].to_string(), "1/2");
+        assert_eq!(solve_linear_int(&a, &[1, 1]), Err(MathError::NotInteger));
+
+        let a = vec![vec![1, 2], vec![2, 4]];
+        assert_eq!(solve_linear(&a, &[1, 2]), Err(MathError::Singular));
+    }
+
+    #[test]
+    fn normalizes_rationals() {
+        let r = Rational::new(4, -6).unwrap();
+        assert_eq!((r.numerator(), r.denominator()), (-2, 3));
+        assert_eq!(Rational::new(1, 0), None);
+        let sum = r.checked_add(Rational::new(2, 3).unwrap()).unwrap();
+        assert_eq!(sum.to_integer(), Some(0));
+    }
+}
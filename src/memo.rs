@@ -0,0 +1,169 @@
+/// Memoization for recursive solutions.
+///
+/// A [`Memo`] is passed down through the recursion instead of a hand-rolled `HashMap`, e.g.
+/// `memo.get_or_compute(n, |memo| fib(n - 1, memo) + fib(n - 2, memo))`.
+///
+/// With `--verbose`, a memo prints its hit and miss counts when it is dropped.
+use std::fmt::{self, Display};
+use std::hash::Hash;
+
+use crate::aoc_debug;
+use crate::hash::FastMap;
+
+pub struct Memo<K, V> {
+    name: &'static str,
+    cache: FastMap<K, V>,
+    stats: Stats,
+}
+
+/// Cache statistics of a [`Memo`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl Stats {
+    /// The fraction of lookups answered from the cache.
+    #[must_use]
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} hits, {} misses ({:.1}% hit rate)",
+            self.hits,
+            self.misses,
+            self.hit_rate() * 100.0
+        )
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> Memo<K, V> {
+    /// Create an empty memo. `name` is used in the statistics printed with `--verbose`.
+    #[must_use]
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            cache: FastMap::default(),
+            stats: Stats::default(),
+        }
+    }
+
+    /// Return the cached value for `key`, or compute it with `compute` and cache it. `compute` gets the memo
+    /// back so that it can recurse.
+    pub fn get_or_compute(&mut self, key: K, compute: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(value) = self.cache.get(&key) {
+            self.stats.hits += 1;
+            return value.clone();
+        }
+        self.stats.misses += 1;
+        let value = compute(self);
+        self.cache.insert(key, value.clone());
+        value
+    }
+
+    /// Like [`Memo::get_or_compute`] for computations that can fail. Errors are not cached.
+    pub fn try_get_or_compute<E>(
+        &mut self,
+        key: K,
+        compute: impl FnOnce(&mut Self) -> Result<V, E>,
+    ) -> Result<V, E> {
+        if let Some(value) = self.cache.get(&key) {
+            self.stats.hits += 1;
+            return Ok(value.clone());
+        }
+        self.stats.misses += 1;
+        let value = compute(self)?;
+        self.cache.insert(key, value.clone());
+        Ok(value)
+    }
+}
+
+impl<K, V> Memo<K, V> {
+    #[must_use]
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Number of cached values.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+}
+
+impl<K, V> Drop for Memo<K, V> {
+    fn drop(&mut self) {
+        aoc_debug!("memo {}: {}, {} entries", self.name, self.stats, self.len());
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{Memo, Stats};
+
+    fn paths(x: u32, y: u32, memo: &mut Memo<(u32, u32), u64>) -> u64 {
+        memo.get_or_compute((x, y), |memo| {
+            if x == 0 || y == 0 {
+                1
+            } else {
+                paths(x - 1, y, memo) + paths(x, y - 1, memo)
+            }
+        })
+    }
+
+    fn fib(n: u64, memo: &mut Memo<u64, u64>) -> u64 {
+        memo.get_or_compute(n, |memo| {
+            if n < 2 {
+                n
+            } else {
+                fib(n - 1, memo) + fib(n - 2, memo)
+            }
+        })
+    }
+
+    #[test]
+    fn computes_fibonacci() {
+        assert_eq!(fib(90, &mut Memo::new("fib")), 2_880_067_194_370_816_120);
+    }
+
+    #[test]
+    fn caches_recursive_calls() {
+        let mut memo = Memo::new("paths");
+        assert_eq!(paths(16, 16, &mut memo), 601_080_390);
+        assert_eq!(memo.len(), 17 * 17 - 1);
+        assert_eq!(memo.stats().misses, memo.len() as u64);
+        let hits = memo.stats().hits;
+        assert!(hits > 0);
+
+        assert_eq!(paths(16, 16, &mut memo), 601_080_390);
+        assert_eq!(memo.stats().hits, hits + 1);
+    }
+
+    #[test]
+    fn does_not_cache_errors() {
+        let mut memo: Memo<u32, u32> = Memo::new("errors");
+        assert_eq!(memo.try_get_or_compute(1, |_| Err("boom")), Err("boom"));
+        assert!(memo.is_empty());
+        assert_eq!(memo.try_get_or_compute(1, |_| Ok::<_, &str>(7)), Ok(7));
+        assert_eq!(memo.try_get_or_compute(1, |_| Err("boom")), Ok(7));
+        assert_eq!(memo.stats(), Stats { hits: 1, misses: 2 });
+    }
+}
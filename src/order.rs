@@ -0,0 +1,241 @@
+/// Partial orders given as "`a` comes before `b`" rules, stored as a dependency graph.
+///
+/// The rules of a puzzle are often only consistent for the subset of items that appear together, so besides a
+/// topological sort of the whole graph there are checks and sorts for subsets.
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, VecDeque};
+use std::fmt::{self, Debug, Display};
+
+use petgraph::algo;
+use petgraph::graphmap::{DiGraphMap, NodeTrait};
+use petgraph::Direction::Incoming;
+
+use crate::hash::FastMap;
+
+/// A cycle in the rules, starting and ending with the same item.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cycle<N>(pub Vec<N>);
+
+impl<N: Display> Display for Cycle<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rules contain a cycle: ")?;
+        for (i, node) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " -> ")?;
+            }
+            write!(f, "{node}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<N: Debug + Display> std::error::Error for Cycle<N> {}
+
+#[derive(Clone, Debug, Default)]
+pub struct Order<N: NodeTrait> {
+    graph: DiGraphMap<N, ()>,
+}
+
+impl<N: NodeTrait> Order<N> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            graph: DiGraphMap::new(),
+        }
+    }
+
+    /// Build an order from `(before, after)` pairs.
+    pub fn from_rules(rules: impl IntoIterator<Item = (N, N)>) -> Self {
+        let mut order = Self::new();
+        for (before, after) in rules {
+            order.add_rule(before, after);
+        }
+        order
+    }
+
+    /// Require `before` to come before `after`.
+    pub fn add_rule(&mut self, before: N, after: N) {
+        self.graph.add_edge(before, after, ());
+    }
+
+    /// Add an item without any rules.
+    pub fn add_item(&mut self, item: N) {
+        self.graph.add_node(item);
+    }
+
+    /// Whether there is a rule that `before` comes before `after`. Rules are not followed transitively.
+    #[must_use]
+    pub fn has_rule(&self, before: N, after: N) -> bool {
+        self.graph.contains_edge(before, after)
+    }
+
+    /// All items in an order that respects every rule.
+    pub fn toposort(&self) -> Result<Vec<N>, Cycle<N>> {
+        algo::toposort(&self.graph, None)
+            .map_err(|cycle| find_cycle(&self.graph, cycle.node_id()).expect("node is in a cycle"))
+    }
+
+    /// A cycle in the rules, if there is one.
+    #[must_use]
+    pub fn find_cycle(&self) -> Option<Cycle<N>> {
+        let start = algo::toposort(&self.graph, None).err()?.node_id();
+        find_cycle(&self.graph, start)
+    }
+
+    /// The first pair `(a, b)` of `sequence` where `a` comes first although a rule says `b` comes before `a`.
+    #[must_use]
+    pub fn violation(&self, sequence: &[N]) -> Option<(N, N)> {
+        sequence.iter().enumerate().find_map(|(i, &a)| {
+            sequence[i + 1..]
+                .iter()
+                .find(|&&b| self.has_rule(b, a))
+                .map(|&b| (a, b))
+        })
+    }
+
+    /// Whether no rule between items of `sequence` is broken.
+    #[must_use]
+    pub fn respects(&self, sequence: &[N]) -> bool {
+        self.violation(sequence).is_none()
+    }
+
+    /// Sort `items` using only the rules between them. Items that are not ordered by any rule keep their
+    /// relative order, so the result is the same no matter how the rules were added.
+    pub fn sort_subset(&self, items: &[N]) -> Result<Vec<N>, Cycle<N>> {
+        let subset = self.subset(items);
+        let position: FastMap<N, usize> = items.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+
+        // Kahn's algorithm, always taking the earliest available item.
+        let mut in_degree: FastMap<N, usize> = items
+            .iter()
+            .map(|&n| (n, subset.neighbors_directed(n, Incoming).count()))
+            .collect();
+        let mut ready: BinaryHeap<Reverse<usize>> = items
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| in_degree[n] == 0)
+            .map(|(i, _)| Reverse(i))
+            .collect();
+
+        let mut sorted = Vec::with_capacity(items.len());
+        while let Some(Reverse(i)) = ready.pop() {
+            let node = items[i];
+            sorted.push(node);
+            for next in subset.neighbors(node) {
+                let degree = in_degree.get_mut(&next).expect("node is in the subset");
+                *degree -= 1;
+                if *degree == 0 {
+                    ready.push(Reverse(position[&next]));
+                }
+            }
+        }
+
+        if sorted.len() < items.len() {
+            // Kahn's algorithm only gets stuck on a cycle.
+            let start = algo::toposort(&subset, None)
+                .err()
+                .expect("unsorted items contain a cycle")
+                .node_id();
+            return Err(find_cycle(&subset, start).expect("node is in a cycle"));
+        }
+        Ok(sorted)
+    }
+
+    /// The rules between `items` only.
+    fn subset(&self, items: &[N]) -> DiGraphMap<N, ()> {
+        let mut subset = DiGraphMap::new();
+        for &item in items {
+            subset.add_node(item);
+        }
+        for &a in items {
+            if !self.graph.contains_node(a) {
+                continue;
+            }
+            for b in self.graph.neighbors(a) {
+                if subset.contains_node(b) {
+                    subset.add_edge(a, b, ());
+                }
+            }
+        }
+        subset
+    }
+}
+
+/// The shortest cycle through `start`, found with a BFS back to `start`.
+fn find_cycle<N: NodeTrait>(graph: &DiGraphMap<N, ()>, start: N) -> Option<Cycle<N>> {
+    let mut previous: FastMap<N, N> = FastMap::default();
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        for next in graph.neighbors(node) {
+            if next == start {
+                let mut cycle = vec![start, node];
+                let mut current = node;
+                while current != start {
+                    current = previous[&current];
+                    cycle.push(current);
+                }
+                cycle.reverse();
+                return Some(Cycle(cycle));
+            }
+            if let Entry::Vacant(entry) = previous.entry(next) {
+                entry.insert(node);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{Cycle, Order};
+
+    #[test]
+    fn sorts_topologically() {
+        let order = Order::from_rules([(1, 2), (2, 3), (1, 3), (4, 3)]);
+        let sorted = order.toposort().unwrap();
+        assert!(order.respects(&sorted));
+        assert_eq!(sorted.len(), 4);
+    }
+
+    #[test]
+    fn reports_cycles() {
+        let order = Order::from_rules([(1, 2), (2, 3), (3, 1), (3, 4)]);
+        let cycle = order.toposort().unwrap_err();
+        assert_eq!(cycle.0.len(), 4);
+        assert_eq!(cycle.0.first(), cycle.0.last());
+        assert!(cycle.0.windows(2).all(|w| order.has_rule(w[0], w[1])));
+        assert_eq!(
+            Order::from_rules([(5, 5)]).find_cycle(),
+            Some(Cycle(vec![5, 5]))
+        );
+        assert_eq!(
+            Cycle(vec![1, 2, 1]).to_string(),
+            "rules contain a cycle: 1 -> 2 -> 1"
+        );
+        assert_eq!(Order::from_rules([(1, 2)]).find_cycle(), None);
+    }
+
+    #[test]
+    fn checks_sequences() {
+        let order = Order::from_rules([(47, 53), (97, 13), (97, 47), (75, 53), (61, 13)]);
+        assert!(order.respects(&[75, 47, 61, 53]));
+        assert_eq!(order.violation(&[61, 13, 97]), Some((13, 97)));
+    }
+
+    #[test]
+    fn sorts_subsets_of_cyclic_rules() {
+        // the rules as a whole are a cycle, but every subset of two is ordered.
+        let order = Order::from_rules([(1, 2), (2, 3), (3, 1)]);
+        assert!(order.toposort().is_err());
+        assert_eq!(order.sort_subset(&[2, 1]), Ok(vec![1, 2]));
+        assert_eq!(order.sort_subset(&[3, 2]), Ok(vec![2, 3]));
+        // unrelated items keep their order.
+        assert_eq!(order.sort_subset(&[9, 3, 8, 2]), Ok(vec![9, 8, 2, 3]));
+        let cycle = order.sort_subset(&[1, 2, 3]).unwrap_err();
+        assert_eq!(cycle.0.len(), 4);
+    }
+}
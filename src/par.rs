@@ -0,0 +1,133 @@
+/// Data parallelism for parts that do the same independent work for many items.
+///
+/// The items are split into one contiguous chunk per thread, and the chunks run on scoped threads, so closures
+/// can borrow from the caller. Results keep the order of the items.
+///
+/// The thread count defaults to the available parallelism and can be set with `--threads`. The runner prints it
+/// next to the timing of parts that used this module.
+use std::iter::Sum;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+/// 0 means "use the available parallelism".
+static THREADS: AtomicUsize = AtomicUsize::new(0);
+static USED: AtomicBool = AtomicBool::new(false);
+
+/// Number of threads that work is split across.
+#[must_use]
+pub fn threads() -> usize {
+    match THREADS.load(Ordering::Relaxed) {
+        0 => thread::available_parallelism().map_or(1, std::num::NonZero::get),
+        n => n,
+    }
+}
+
+/// Set the number of threads. 0 resets it to the available parallelism.
+pub fn set_threads(threads: usize) {
+    THREADS.store(threads, Ordering::Relaxed);
+}
+
+/// Whether any of the functions of this module were called since the last call to `take_used`.
+pub fn take_used() -> bool {
+    USED.swap(false, Ordering::Relaxed)
+}
+
+/// `f` applied to every item. Every thread creates its own scratch state with `init`, e.g. a buffer that is
+/// reused between items.
+pub fn par_map_init<T, S, R>(
+    items: &[T],
+    init: impl Fn() -> S + Sync,
+    f: impl Fn(&mut S, &T) -> R + Sync,
+) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+{
+    USED.store(true, Ordering::Relaxed);
+    let threads = threads();
+    if threads <= 1 || items.len() <= 1 {
+        let mut state = init();
+        return items.iter().map(|item| f(&mut state, item)).collect();
+    }
+
+    let chunk_size = items.len().div_ceil(threads);
+    let (init, f) = (&init, &f);
+    thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let mut state = init();
+                    chunk
+                        .iter()
+                        .map(|item| f(&mut state, item))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("worker thread panicked"))
+            .collect()
+    })
+}
+
+/// `f` applied to every item.
+pub fn par_map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    par_map_init(items, || (), |(), item| f(item))
+}
+
+/// `f` applied to every index of `range`.
+pub fn par_map_range<R: Send>(range: Range<usize>, f: impl Fn(usize) -> R + Sync) -> Vec<R> {
+    let indices: Vec<usize> = range.collect();
+    par_map(&indices, |&i| f(i))
+}
+
+/// The sum of `f` over all items.
+pub fn par_sum<T: Sync, R: Send + Sum>(items: &[T], f: impl Fn(&T) -> R + Sync) -> R {
+    par_map(items, f).into_iter().sum()
+}
+
+/// The sum of `f` over every index of `range`.
+pub fn par_sum_range<R: Send + Sum>(range: Range<usize>, f: impl Fn(usize) -> R + Sync) -> R {
+    par_map_range(range, f).into_iter().sum()
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{par_map, par_map_init, par_map_range, par_sum, par_sum_range, threads};
+
+    #[test]
+    fn keeps_order() {
+        let items: Vec<u64> = (0..1000).collect();
+        assert_eq!(
+            par_map(&items, |x| x * 2),
+            (0..2000).step_by(2).collect::<Vec<_>>()
+        );
+        assert_eq!(par_map_range(3..6, |i| i * i), [9, 16, 25]);
+        assert!(par_map(&[] as &[u64], |x| *x).is_empty());
+        assert!(threads() >= 1);
+    }
+
+    #[test]
+    fn sums() {
+        let items: Vec<u64> = (1..=100).collect();
+        assert_eq!(par_sum(&items, |x| *x), 5050);
+        assert_eq!(par_sum_range(0..0, |i| i), 0);
+        assert_eq!(par_sum_range(1..101, |i| i as u64), 5050);
+    }
+
+    #[test]
+    fn reuses_state_per_thread() {
+        let items: Vec<usize> = (0..100).collect();
+        let lengths = par_map_init(&items, Vec::new, |buffer: &mut Vec<usize>, &i| {
+            buffer.clear();
+            buffer.extend(0..i);
+            buffer.len()
+        });
+        assert_eq!(lengths, items);
+    }
+}
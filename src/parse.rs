@@ -0,0 +1,342 @@
+/// Parsers for the common shapes of puzzle inputs.
+///
+/// Errors point to the line and column of the offending input instead of panicking, so that solutions can pass
+/// them on with `?` or `map_err`.
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// An error with a 1-based line and column in the input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A line of the input that knows its position, so that errors can point into the input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Line<'a> {
+    /// 1-based line number.
+    pub number: usize,
+    pub text: &'a str,
+}
+
+impl Line<'_> {
+    /// Create an error at the 1-based `column` of this line.
+    #[must_use]
+    pub fn error(&self, column: usize, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: self.number,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// Create an error at the start of the sub-slice `part` of this line.
+    fn error_at(&self, part: &str, message: impl Into<String>) -> ParseError {
+        self.error(self.column_of(part), message)
+    }
+
+    fn column_of(&self, part: &str) -> usize {
+        let offset = (part.as_ptr() as usize)
+            .saturating_sub(self.text.as_ptr() as usize)
+            .min(self.text.len());
+        self.text[..offset].chars().count() + 1
+    }
+
+    /// All signed integers in the line, ignoring any other text. A `-` is only read as a sign if it does not
+    /// follow a letter or digit, so that `p=0,-4` gives `[0, -4]` and `2-4` gives `[2, 4]`.
+    pub fn ints(&self) -> Result<Vec<i64>, ParseError> {
+        int_spans(self.text)
+            .map(|span| {
+                span.parse()
+                    .map_err(|e| self.error_at(span, format!("couldn't parse \"{span}\": {e}")))
+            })
+            .collect()
+    }
+
+    /// Exactly `N` signed integers, see [`Line::ints`].
+    pub fn fixed<const N: usize>(&self) -> Result<[i64; N], ParseError> {
+        let ints = self.ints()?;
+        let found = ints.len();
+        ints.try_into()
+            .map_err(|_| self.error(1, format!("expected {N} numbers, found {found}")))
+    }
+
+    /// Split the line once at `separator` and parse both sides, e.g. `47|53`.
+    pub fn pair<A, B>(&self, separator: &str) -> Result<(A, B), ParseError>
+    where
+        A: FromStr,
+        B: FromStr,
+        A::Err: Display,
+        B::Err: Display,
+    {
+        let (a, b) = self.text.split_once(separator).ok_or_else(|| {
+            self.error(
+                1,
+                format!("expected two values separated by \"{separator}\""),
+            )
+        })?;
+        Ok((self.value(a)?, self.value(b)?))
+    }
+
+    /// Parse values separated by `separator`, e.g. `75,47,61`.
+    pub fn list<T>(&self, separator: &str) -> Result<Vec<T>, ParseError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.text
+            .split(separator)
+            .map(|part| self.value(part))
+            .collect()
+    }
+
+    /// Parse a key and a list of values separated by whitespace or commas, e.g. `190: 10 19`.
+    pub fn key_values<K, V>(&self, separator: &str) -> Result<(K, Vec<V>), ParseError>
+    where
+        K: FromStr,
+        V: FromStr,
+        K::Err: Display,
+        V::Err: Display,
+    {
+        let (key, values) = self
+            .text
+            .split_once(separator)
+            .ok_or_else(|| self.error(1, format!("expected a key followed by \"{separator}\"")))?;
+        let values = values
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|v| !v.is_empty())
+            .map(|v| self.value(v))
+            .collect::<Result<_, _>>()?;
+        Ok((self.value(key)?, values))
+    }
+
+    /// Parse a part of this line, ignoring surrounding whitespace.
+    pub fn value<T>(&self, part: &str) -> Result<T, ParseError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let part = part.trim();
+        part.parse()
+            .map_err(|e| self.error_at(part, format!("couldn't parse \"{part}\": {e}")))
+    }
+}
+
+/// A group of lines between blank lines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Block<'a> {
+    /// 1-based number of the first line of the block.
+    pub first_line: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Block<'a> {
+    pub fn lines(&self) -> impl Iterator<Item = Line<'a>> {
+        let first_line = self.first_line;
+        self.text.lines().enumerate().map(move |(i, text)| Line {
+            number: first_line + i,
+            text,
+        })
+    }
+}
+
+/// All lines of the input, numbered from 1.
+pub fn lines(input: &str) -> impl Iterator<Item = Line<'_>> {
+    Block {
+        first_line: 1,
+        text: input,
+    }
+    .lines()
+}
+
+/// The blocks of the input, split at blank lines. Runs of blank lines count as a single separator.
+#[must_use]
+pub fn blocks(input: &str) -> Vec<Block<'_>> {
+    let mut blocks = vec![];
+    let mut start: Option<(usize, usize)> = None;
+    let mut offset = 0;
+
+    for (i, line) in input.split_inclusive('\n').enumerate() {
+        let is_blank = line.trim().is_empty();
+        match (start, is_blank) {
+            (None, false) => start = Some((i + 1, offset)),
+            (Some((first_line, from)), true) => {
+                blocks.push(Block {
+                    first_line,
+                    text: input[from..offset].trim_end_matches(['\n', '\r']),
+                });
+                start = None;
+            }
+            _ => {}
+        }
+        offset += line.len();
+    }
+    if let Some((first_line, from)) = start {
+        blocks.push(Block {
+            first_line,
+            text: input[from..].trim_end_matches(['\n', '\r']),
+        });
+    }
+    blocks
+}
+
+/// All signed integers of every line, see [`Line::ints`].
+pub fn ints_per_line(input: &str) -> Result<Vec<Vec<i64>>, ParseError> {
+    lines(input).map(|line| line.ints()).collect()
+}
+
+/// Exactly `N` signed integers on every non-empty line, e.g. `3   4` or `p=0,4 v=3,-3`.
+pub fn fixed_per_line<const N: usize>(input: &str) -> Result<Vec<[i64; N]>, ParseError> {
+    lines(input)
+        .filter(|line| !line.text.trim().is_empty())
+        .map(|line| line.fixed())
+        .collect()
+}
+
+/// A pair of values on every non-empty line, see [`Line::pair`].
+pub fn pairs<A, B>(input: &str, separator: &str) -> Result<Vec<(A, B)>, ParseError>
+where
+    A: FromStr,
+    B: FromStr,
+    A::Err: Display,
+    B::Err: Display,
+{
+    lines(input)
+        .filter(|line| !line.text.trim().is_empty())
+        .map(|line| line.pair(separator))
+        .collect()
+}
+
+/// A key and its values on every non-empty line, see [`Line::key_values`].
+pub fn key_values<K, V>(input: &str, separator: &str) -> Result<Vec<(K, Vec<V>)>, ParseError>
+where
+    K: FromStr,
+    V: FromStr,
+    K::Err: Display,
+    V::Err: Display,
+{
+    lines(input)
+        .filter(|line| !line.text.trim().is_empty())
+        .map(|line| line.key_values(separator))
+        .collect()
+}
+
+/// Sub-slices of `text` that look like signed integers.
+fn int_spans(text: &str) -> impl Iterator<Item = &str> {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    std::iter::from_fn(move || {
+        while i < bytes.len() {
+            let is_sign = bytes[i] == b'-'
+                && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)
+                && (i == 0 || !bytes[i - 1].is_ascii_alphanumeric());
+            if is_sign || bytes[i].is_ascii_digit() {
+                let start = i;
+                i += 1;
+                while i < bytes.len() && bytes[i].is_ascii_digit() {
+                    i += 1;
+                }
+                return Some(&text[start..i]);
+            }
+            i += 1;
+        }
+        None
+    })
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{blocks, fixed_per_line, ints_per_line, key_values, lines, pairs, ParseError};
+
+    #[test]
+    fn extracts_signed_ints() {
+        assert_eq!(
+            ints_per_line("p=0,4 v=3,-3\nButton A: X+94, Y+34\n2-4,6-8\n"),
+            Ok(vec![vec![0, 4, 3, -3], vec![94, 34], vec![2, 4, 6, 8]])
+        );
+        assert_eq!(ints_per_line("- 12 -"), Ok(vec![vec![12]]));
+    }
+
+    #[test]
+    fn reports_overflow_position() {
+        assert_eq!(
+            ints_per_line("1\nx=99999999999999999999"),
+            Err(ParseError {
+                line: 2,
+                column: 3,
+                message: "couldn't parse \"99999999999999999999\": number too large to fit in target type".into()
+            })
+        );
+    }
+
+    #[test]
+    fn parses_fixed_arity() {
+        assert_eq!(
+            fixed_per_line::<2>("3   4\n4   3\n\n"),
+            Ok(vec![[3, 4], [4, 3]])
+        );
+        let err = fixed_per_line::<2>("3   4\n4\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 1: expected 2 numbers, found 1"
+        );
+    }
+
+    #[test]
+    fn splits_blocks() {
+        let input = "a\nb\n\n\nc\n\nd\ne\n";
+        let blocks = blocks(input);
+        assert_eq!(blocks.len(), 3);
+        assert_eq!((blocks[0].first_line, blocks[0].text), (1, "a\nb"));
+        assert_eq!((blocks[1].first_line, blocks[1].text), (5, "c"));
+        let numbers: Vec<usize> = blocks[2].lines().map(|l| l.number).collect();
+        assert_eq!(numbers, [7, 8]);
+    }
+
+    #[test]
+    fn parses_pairs() {
+        assert_eq!(
+            pairs::<u32, u32>("47|53\n97|13", "|"),
+            Ok(vec![(47, 53), (97, 13)])
+        );
+        let err = pairs::<u32, u32>("47|53\n97|x3", "|").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 4));
+        let err = pairs::<u32, u32>("47-53", "|").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 1));
+    }
+
+    #[test]
+    fn parses_key_values() {
+        assert_eq!(
+            key_values::<u64, u64>("190: 10 19\n3267: 81, 40 27", ":"),
+            Ok(vec![(190, vec![10, 19]), (3267, vec![81, 40, 27])])
+        );
+        let err = key_values::<u64, u64>("190: 10 1a9", ":").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 9));
+    }
+
+    #[test]
+    fn parses_lists() {
+        let line = lines("75,47,61").next().unwrap();
+        assert_eq!(line.list::<u32>(","), Ok(vec![75, 47, 61]));
+        let line = lines("\n75,x,61").nth(1).unwrap();
+        assert_eq!(line.list::<u32>(",").unwrap_err().column, 4);
+    }
+}
@@ -0,0 +1,433 @@
+/// Sets and maps of half-open integer ranges.
+///
+/// Both keep their ranges sorted and disjoint in a `BTreeMap`, so inserts, removals and lookups take
+/// `O(log n)` plus the number of ranges that are merged or split.
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
+
+/// A set of integers, stored as disjoint, non-adjacent ranges.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RangeSet {
+    /// start -> end
+    ranges: BTreeMap<i64, i64>,
+    /// length -> starts of the ranges with that length, for [`RangeSet::first_fit`]
+    starts_by_len: BTreeMap<i64, BTreeSet<i64>>,
+}
+
+impl RangeSet {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A set of the values of all `ranges`.
+    ///
+    /// Ranges that overlap or touch end to start are merged into one, so `0..2` and `2..3` become `0..3`. Empty
+    /// ranges add no values and never keep their neighbours apart.
+    pub fn from_ranges(ranges: impl IntoIterator<Item = Range<i64>>) -> Self {
+        let mut set = Self::new();
+        for range in ranges {
+            set.insert(range);
+        }
+        set
+    }
+
+    /// Add `range`, merging it with overlapping and adjacent ranges.
+    pub fn insert(&mut self, range: Range<i64>) {
+        if range.is_empty() {
+            return;
+        }
+        let Range { mut start, mut end } = range;
+        if let Some((&s, &e)) = self.ranges.range(..=start).next_back() {
+            if e >= start {
+                start = s;
+                end = end.max(e);
+            }
+        }
+        while let Some((&s, &e)) = self.ranges.range(start..=end).next() {
+            end = end.max(e);
+            self.take(s);
+        }
+        self.put(start, end);
+    }
+
+    /// Remove `range`, splitting ranges that stick out on either side.
+    pub fn remove(&mut self, range: Range<i64>) {
+        if range.is_empty() {
+            return;
+        }
+        if let Some((&s, &e)) = self.ranges.range(..range.start).next_back() {
+            if e > range.start {
+                self.take(s);
+                self.put(s, range.start);
+                if e > range.end {
+                    self.put(range.end, e);
+                }
+            }
+        }
+        while let Some((&s, &e)) = self.ranges.range(range.clone()).next() {
+            self.take(s);
+            if e > range.end {
+                self.put(range.end, e);
+            }
+        }
+    }
+
+    /// Add a range that doesn't touch any other range.
+    fn put(&mut self, start: i64, end: i64) {
+        self.ranges.insert(start, end);
+        self.starts_by_len
+            .entry(end - start)
+            .or_default()
+            .insert(start);
+    }
+
+    /// Remove the range that begins at `start`.
+    fn take(&mut self, start: i64) {
+        let Some(end) = self.ranges.remove(&start) else {
+            return;
+        };
+        if let Some(starts) = self.starts_by_len.get_mut(&(end - start)) {
+            starts.remove(&start);
+            if starts.is_empty() {
+                self.starts_by_len.remove(&(end - start));
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn contains(&self, value: i64) -> bool {
+        self.range_of(value).is_some()
+    }
+
+    /// The range that contains `value`.
+    #[must_use]
+    pub fn range_of(&self, value: i64) -> Option<Range<i64>> {
+        let (&start, &end) = self.ranges.range(..=value).next_back()?;
+        (value < end).then_some(start..end)
+    }
+
+    /// Whether any value of `range` is in the set.
+    #[must_use]
+    pub fn overlaps(&self, range: &Range<i64>) -> bool {
+        !range.is_empty()
+            && (self.contains(range.start) || self.ranges.range(range.clone()).next().is_some())
+    }
+
+    /// The first range, by start, that is at least `len` long.
+    ///
+    /// Ranges are indexed by length, so this only looks at the first range of each length that fits. That is
+    /// `O(k log n)` for `k` distinct lengths.
+    #[must_use]
+    pub fn first_fit(&self, len: i64) -> Option<Range<i64>> {
+        let start = self
+            .starts_by_len
+            .range(len.max(1)..)
+            .filter_map(|(_, starts)| starts.first())
+            .min()?;
+        Some(*start..self.ranges[start])
+    }
+
+    /// Ranges in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = Range<i64>> + '_ {
+        self.ranges.iter().map(|(&start, &end)| start..end)
+    }
+
+    /// Number of disjoint ranges.
+    #[must_use]
+    pub fn range_count(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Number of values in the set.
+    #[must_use]
+    pub fn len(&self) -> i64 {
+        self.iter().map(|range| range.end - range.start).sum()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The values of `bounds` that are not in the set.
+    #[must_use]
+    pub fn gaps(&self, bounds: Range<i64>) -> RangeSet {
+        let mut gaps = RangeSet::from_ranges([bounds]);
+        for range in self.iter() {
+            gaps.remove(range);
+        }
+        gaps
+    }
+
+    #[must_use]
+    pub fn union(&self, other: &RangeSet) -> RangeSet {
+        let mut union = self.clone();
+        for range in other.iter() {
+            union.insert(range);
+        }
+        union
+    }
+
+    #[must_use]
+    pub fn intersection(&self, other: &RangeSet) -> RangeSet {
+        let mut intersection = self.clone();
+        let (Some((&start, _)), Some((_, &end))) =
+            (self.ranges.first_key_value(), self.ranges.last_key_value())
+        else {
+            return intersection;
+        };
+        for gap in other.gaps(start..end).iter() {
+            intersection.remove(gap);
+        }
+        intersection
+    }
+
+    #[must_use]
+    pub fn difference(&self, other: &RangeSet) -> RangeSet {
+        let mut difference = self.clone();
+        for range in other.iter() {
+            difference.remove(range);
+        }
+        difference
+    }
+}
+
+/// Values assigned to disjoint ranges. Adjacent ranges with equal values are merged.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangeMap<V> {
+    /// start -> (end, value)
+    ranges: BTreeMap<i64, (i64, V)>,
+}
+
+impl<V> Default for RangeMap<V> {
+    fn default() -> Self {
+        Self {
+            ranges: BTreeMap::new(),
+        }
+    }
+}
+
+impl<V: Clone + PartialEq> RangeMap<V> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assign `value` to `range`, overwriting previous values.
+    pub fn insert(&mut self, range: Range<i64>, value: V) {
+        if range.is_empty() {
+            return;
+        }
+        self.remove(range.clone());
+        let Range { mut start, mut end } = range;
+
+        // merge with equal neighbours
+        if let Some((&s, (e, v))) = self.ranges.range(..start).next_back() {
+            if *e == start && *v == value {
+                start = s;
+            }
+        }
+        if let Some((e, v)) = self.ranges.get(&end) {
+            if *v == value {
+                let e = *e;
+                self.ranges.remove(&end);
+                end = e;
+            }
+        }
+        self.ranges.insert(start, (end, value));
+    }
+
+    /// Remove values from `range`, splitting ranges that stick out on either side.
+    pub fn remove(&mut self, range: Range<i64>) {
+        if range.is_empty() {
+            return;
+        }
+        self.split_at(range.start);
+        self.split_at(range.end);
+        let keys: Vec<i64> = self.ranges.range(range).map(|(&s, _)| s).collect();
+        for key in keys {
+            self.ranges.remove(&key);
+        }
+    }
+
+    /// Split the range that contains `at` into two ranges with the same value, so that `at` starts a range.
+    pub fn split_at(&mut self, at: i64) {
+        let Some((&start, (end, value))) = self.ranges.range(..at).next_back() else {
+            return;
+        };
+        if at < *end {
+            let (end, value) = (*end, value.clone());
+            self.ranges.insert(start, (at, value.clone()));
+            self.ranges.insert(at, (end, value));
+        }
+    }
+
+    #[must_use]
+    pub fn get(&self, position: i64) -> Option<&V> {
+        self.get_range(position).map(|(_, value)| value)
+    }
+
+    /// The range that contains `position` and its value.
+    #[must_use]
+    pub fn get_range(&self, position: i64) -> Option<(Range<i64>, &V)> {
+        let (&start, (end, value)) = self.ranges.range(..=position).next_back()?;
+        (position < *end).then_some((start..*end, value))
+    }
+
+    /// Ranges and their values in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = (Range<i64>, &V)> + '_ {
+        self.ranges
+            .iter()
+            .map(|(&start, (end, value))| (start..*end, value))
+    }
+
+    /// Number of ranges.
+    #[must_use]
+    pub fn range_count(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Number of values that have a value assigned.
+    #[must_use]
+    pub fn covered(&self) -> i64 {
+        self.iter().map(|(range, _)| range.end - range.start).sum()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+}
+
+/// How many of `ranges` cover each value. Values that no range covers are not in the map.
+pub fn coverage(ranges: impl IntoIterator<Item = Range<i64>>) -> RangeMap<usize> {
+    let mut deltas: BTreeMap<i64, i64> = BTreeMap::new();
+    for range in ranges.into_iter().filter(|r| !r.is_empty()) {
+        *deltas.entry(range.start).or_default() += 1;
+        *deltas.entry(range.end).or_default() -= 1;
+    }
+
+    let mut coverage = RangeMap::new();
+    let mut count = 0;
+    let mut previous = None;
+    for (position, delta) in deltas {
+        if let Some(start) = previous {
+            if count > 0 {
+                let count = usize::try_from(count).expect("coverage is positive");
+                coverage.insert(start..position, count);
+            }
+        }
+        count += delta;
+        previous = Some(position);
+    }
+    coverage
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{coverage, RangeMap, RangeSet};
+
+    #[test]
+    fn merges_ranges() {
+        let mut set = RangeSet::from_ranges([3..5, 10..14, 16..20, 12..18]);
+        assert_eq!(set.iter().collect::<Vec<_>>(), [3..5, 10..20]);
+        set.insert(5..7);
+        assert_eq!(set.iter().collect::<Vec<_>>(), [3..7, 10..20]);
+        assert_eq!(set.len(), 14);
+        assert!(set.contains(19));
+        assert!(!set.contains(20));
+        assert_eq!(set.range_of(12), Some(10..20));
+        assert!(set.overlaps(&(0..4)));
+        assert!(!set.overlaps(&(7..10)));
+    }
+
+    #[test]
+    fn merges_touching_ranges() {
+        let set = RangeSet::from_ranges([0..2, 2..2, 2..3, 5..5, 5..7]);
+        assert_eq!(set.iter().collect::<Vec<_>>(), [0..3, 5..7]);
+        assert_eq!(set.first_fit(3), Some(0..3));
+    }
+
+    #[test]
+    fn splits_on_remove() {
+        let mut set = RangeSet::from_ranges([0..10, 20..30]);
+        set.remove(5..25);
+        assert_eq!(set.iter().collect::<Vec<_>>(), [0..5, 25..30]);
+        set.remove(1..2);
+        assert_eq!(set.iter().collect::<Vec<_>>(), [0..1, 2..5, 25..30]);
+        assert_eq!(set.range_count(), 3);
+        assert_eq!(set.first_fit(3), Some(2..5));
+        assert_eq!(set.first_fit(6), None);
+    }
+
+    #[test]
+    fn finds_first_fit_after_changes() {
+        let mut set = RangeSet::from_ranges([0..2, 4..9, 12..15, 20..29]);
+        assert_eq!(set.first_fit(0), Some(0..2));
+        assert_eq!(set.first_fit(4), Some(4..9));
+        set.remove(4..6);
+        assert_eq!(set.first_fit(4), Some(20..29));
+        assert_eq!(set.first_fit(3), Some(6..9));
+        set.insert(9..12);
+        assert_eq!(set.first_fit(4), Some(6..15));
+        set.remove(0..30);
+        assert_eq!(set.first_fit(1), None);
+        assert!(set.is_empty());
+        assert_eq!(set, RangeSet::new());
+    }
+
+    #[test]
+    fn combines_sets() {
+        let a = RangeSet::from_ranges([0..10, 20..30]);
+        let b = RangeSet::from_ranges(std::iter::once(5..25));
+        let union = a.union(&b);
+        assert_eq!((union.range_count(), union.range_of(0)), (1, Some(0..30)));
+        assert_eq!(
+            a.intersection(&b).iter().collect::<Vec<_>>(),
+            [5..10, 20..25]
+        );
+        assert_eq!(a.difference(&b).iter().collect::<Vec<_>>(), [0..5, 25..30]);
+        assert_eq!(
+            a.gaps(-5..35).iter().collect::<Vec<_>>(),
+            [-5..0, 10..20, 30..35]
+        );
+    }
+
+    #[test]
+    fn maps_ranges() {
+        // 00...111....22
+        let mut disk = RangeMap::new();
+        disk.insert(0..14, None);
+        disk.insert(0..2, Some(0));
+        disk.insert(5..8, Some(1));
+        disk.insert(12..14, Some(2));
+        assert_eq!(disk.range_count(), 5);
+        assert_eq!(disk.get_range(3), Some((2..5, &None)));
+        assert_eq!(disk.get_range(8), Some((8..12, &None)));
+
+        // move file 2 into the first gap: 0022.111......
+        disk.insert(12..14, None);
+        disk.insert(2..4, Some(2));
+        assert_eq!(disk.get(3), Some(&Some(2)));
+        assert_eq!(disk.get_range(10), Some((8..14, &None)));
+        assert_eq!(disk.get(14), None);
+        assert_eq!(disk.covered(), 14);
+
+        disk.split_at(10);
+        assert_eq!(disk.get_range(10), Some((10..14, &None)));
+        disk.remove(0..4);
+        assert_eq!(disk.iter().next(), Some((4..5, &None)));
+    }
+
+    #[test]
+    fn counts_coverage() {
+        let coverage = coverage([0..10, 5..15, 5..8, 20..25]);
+        assert_eq!(
+            coverage.iter().map(|(r, c)| (r, *c)).collect::<Vec<_>>(),
+            [(0..5, 1), (5..8, 3), (8..10, 2), (10..15, 1), (20..25, 1)]
+        );
+    }
+}
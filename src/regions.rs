@@ -0,0 +1,364 @@
+/// Connected regions of a grid, and the union-find structure used to find them.
+///
+/// Regions are 4-connected. Each [`Region`] knows its area, perimeter, number of sides and bounding box, so
+/// region puzzles don't need to walk edges themselves. Holes are only counted when asked for.
+use crate::grid::{Grid, Pos, DELTAS4, DELTAS8};
+
+/// Disjoint sets of the elements `0..n`, with path compression and union by size.
+#[derive(Clone, Debug)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    sets: usize,
+}
+
+impl UnionFind {
+    /// `n` elements, each in its own set.
+    #[must_use]
+    pub fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            sets: n,
+        }
+    }
+
+    /// Number of elements.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Number of disjoint sets.
+    #[must_use]
+    pub fn sets(&self) -> usize {
+        self.sets
+    }
+
+    /// The representative of the set containing `x`.
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut x = x;
+        while self.parent[x] != root {
+            (x, self.parent[x]) = (self.parent[x], root);
+        }
+        root
+    }
+
+    /// Merge the sets containing `a` and `b`. Returns `false` if they were already the same set.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            (a, b) = (b, a);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.sets -= 1;
+        true
+    }
+
+    pub fn same(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// Size of the set containing `x`.
+    pub fn size_of(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+}
+
+/// Inclusive bounding box of a region.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bounds {
+    pub min: Pos,
+    pub max: Pos,
+}
+
+impl Bounds {
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.max.0 - self.min.0 + 1
+    }
+
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.max.1 - self.min.1 + 1
+    }
+
+    #[must_use]
+    pub fn contains(&self, (x, y): Pos) -> bool {
+        (self.min.0..=self.max.0).contains(&x) && (self.min.1..=self.max.1).contains(&y)
+    }
+
+    /// All positions in the box in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let Bounds { min, max } = *self;
+        (min.1..=max.1).flat_map(move |y| (min.0..=max.0).map(move |x| (x, y)))
+    }
+
+    fn extend(&mut self, (x, y): Pos) {
+        self.min = (self.min.0.min(x), self.min.1.min(y));
+        self.max = (self.max.0.max(x), self.max.1.max(y));
+    }
+}
+
+/// A 4-connected region of a grid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Region {
+    /// Index of the region in [`Regions::regions`].
+    pub id: usize,
+    /// Cells in row-major order.
+    pub cells: Vec<Pos>,
+    pub area: usize,
+    /// Number of cell edges between the region and anything else, including the edge of the grid.
+    pub perimeter: usize,
+    /// Number of straight sides of the boundary, which is the same as its number of corners.
+    pub sides: usize,
+    pub bounds: Bounds,
+}
+
+impl Region {
+    /// The first cell in row-major order, i.e. its top left cell.
+    #[must_use]
+    pub fn start(&self) -> Pos {
+        self.cells[0]
+    }
+
+    /// Number of areas that are completely enclosed by the region. `labels` are the [`Regions::labels`] the
+    /// region belongs to.
+    ///
+    /// Outside cells are 8-connected, so a diagonal gap in the region does not enclose anything.
+    #[must_use]
+    pub fn holes(&self, labels: &Grid<usize>) -> usize {
+        count_holes(labels, self)
+    }
+}
+
+/// The regions of a grid and the region of each cell.
+#[derive(Clone, Debug)]
+pub struct Regions {
+    labels: Grid<usize>,
+    regions: Vec<Region>,
+}
+
+impl Regions {
+    /// Regions ordered by their first cell in row-major order.
+    #[must_use]
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Region> + '_ {
+        self.regions.iter()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    /// Region id of every cell.
+    #[must_use]
+    pub fn labels(&self) -> &Grid<usize> {
+        &self.labels
+    }
+
+    /// The region containing `pos`, if it is inside the grid.
+    #[must_use]
+    pub fn region_at(&self, pos: Pos) -> Option<&Region> {
+        self.labels.get(pos).map(|id| &self.regions[*id])
+    }
+}
+
+/// Split the grid into regions of equal, orthogonally adjacent cells.
+pub fn label<T: PartialEq>(grid: &Grid<T>) -> Regions {
+    label_by(grid, |a, b| a == b)
+}
+
+/// Split the grid into regions, joining orthogonal neighbours for which `connected` returns true.
+pub fn label_by<T>(grid: &Grid<T>, mut connected: impl FnMut(&T, &T) -> bool) -> Regions {
+    let width = grid.width();
+    let index = |(x, y): Pos| y * width + x;
+
+    let mut sets = UnionFind::new(width * grid.height());
+    for (pos, cell) in grid.iter() {
+        // Right and down are enough to see every pair once.
+        for next in [grid.step(pos, (1, 0)), grid.step(pos, (0, 1))]
+            .into_iter()
+            .flatten()
+        {
+            if connected(cell, &grid[next]) {
+                sets.union(index(pos), index(next));
+            }
+        }
+    }
+
+    let mut ids = vec![usize::MAX; sets.len()];
+    let mut regions: Vec<Region> = vec![];
+    let mut labels = Grid::new(width, grid.height(), 0);
+    for pos in grid.positions() {
+        let root = sets.find(index(pos));
+        if ids[root] == usize::MAX {
+            ids[root] = regions.len();
+            regions.push(Region {
+                id: regions.len(),
+                cells: vec![],
+                area: 0,
+                perimeter: 0,
+                sides: 0,
+                bounds: Bounds { min: pos, max: pos },
+            });
+        }
+        labels[pos] = ids[root];
+    }
+
+    for pos in labels.positions() {
+        let id = labels[pos];
+        let inside = |delta| labels.step(pos, delta).is_some_and(|p| labels[p] == id);
+        let region = &mut regions[id];
+
+        region.cells.push(pos);
+        region.area += 1;
+        region.bounds.extend(pos);
+        region.perimeter += DELTAS4.iter().filter(|d| !inside(**d)).count();
+        // Every corner of the boundary is at a corner of one of its cells: either convex, with both
+        // neighbours outside, or concave, with both neighbours inside but the diagonal outside.
+        region.sides += [(1, 1), (1, -1), (-1, 1), (-1, -1)]
+            .iter()
+            .filter(|&&(dx, dy)| {
+                let (a, b) = (inside((dx, 0)), inside((0, dy)));
+                (!a && !b) || (a && b && !inside((dx, dy)))
+            })
+            .count();
+    }
+
+    Regions { labels, regions }
+}
+
+/// Count the areas inside the bounding box of `region` that can't reach the outside without crossing it.
+fn count_holes(labels: &Grid<usize>, region: &Region) -> usize {
+    let Bounds { min, .. } = region.bounds;
+    // Pad the bounding box by one cell on each side so that the outside is connected.
+    let (width, height) = (region.bounds.width() + 2, region.bounds.height() + 2);
+    let mut outside = Grid::new(width, height, true);
+    for (x, y) in region.bounds.positions() {
+        if labels[(x, y)] == region.id {
+            outside[(x - min.0 + 1, y - min.1 + 1)] = false;
+        }
+    }
+
+    let mut seen = Grid::new(width, height, false);
+    let flood = |start: Pos, seen: &mut Grid<bool>| {
+        let mut stack = vec![start];
+        seen[start] = true;
+        while let Some(pos) = stack.pop() {
+            for delta in DELTAS8 {
+                if let Some(next) = outside.step(pos, delta) {
+                    if outside[next] && !seen[next] {
+                        seen[next] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+        }
+    };
+
+    flood((0, 0), &mut seen);
+    let mut holes = 0;
+    for pos in outside.positions() {
+        if outside[pos] && !seen[pos] {
+            flood(pos, &mut seen);
+            holes += 1;
+        }
+    }
+    holes
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{label, Bounds, UnionFind};
+    use crate::grid::Grid;
+
+    #[test]
+    fn merges_sets() {
+        let mut sets = UnionFind::new(5);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(3, 4));
+        assert!(!sets.union(1, 0));
+        assert!(sets.same(0, 1));
+        assert!(!sets.same(1, 3));
+        assert_eq!(sets.sets(), 3);
+        assert!(sets.union(1, 4));
+        assert_eq!(sets.size_of(3), 4);
+        assert_eq!(sets.size_of(2), 1);
+    }
+
+    #[test]
+    fn measures_regions() {
+        let grid = Grid::parse_chars("AAAA\nBBCD\nBBCC\nEEEC").unwrap();
+        let regions = label(&grid);
+        let measures: Vec<_> = regions
+            .iter()
+            .map(|r| (grid[r.start()], r.area, r.perimeter, r.sides))
+            .collect();
+        assert_eq!(
+            measures,
+            [
+                ('A', 4, 10, 4),
+                ('B', 4, 8, 4),
+                ('C', 4, 10, 8),
+                ('D', 1, 4, 4),
+                ('E', 3, 8, 4)
+            ]
+        );
+        assert_eq!(
+            regions.region_at((3, 2)).unwrap().bounds,
+            Bounds {
+                min: (2, 1),
+                max: (3, 3)
+            }
+        );
+    }
+
+    #[test]
+    fn finds_holes() {
+        let grid = Grid::parse_chars("OOOOO\nOXOXO\nOOOOO\nOXOXO\nOOOOO").unwrap();
+        let regions = label(&grid);
+        assert_eq!(regions.len(), 5);
+        let outer = &regions.regions()[0];
+        assert_eq!((outer.area, outer.perimeter, outer.sides), (21, 36, 20));
+        assert_eq!(outer.holes(regions.labels()), 4);
+        assert!(regions
+            .iter()
+            .skip(1)
+            .all(|r| r.holes(regions.labels()) == 0));
+
+        // a diagonal gap lets the inside out.
+        let holes = |grid: &str| {
+            let regions = label(&Grid::parse_chars(grid).unwrap());
+            regions.regions()[0].holes(regions.labels())
+        };
+        assert_eq!(holes("#####\n#...#\n#...#\n#...#\n###.#"), 0);
+        assert_eq!(holes("###.\n#..#\n#..#\n####"), 0);
+        assert_eq!(holes("####\n#..#\n####"), 1);
+    }
+}
@@ -0,0 +1,413 @@
+/// Graph searches over any state type.
+///
+/// States are explored through a `successors` closure, so the graph never has to be built up front. For a maze,
+/// the state is usually a position, or a position and a facing when turning has a cost.
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+use crate::hash::{FastMap, FastSet};
+
+/// Costs of weighted searches. `Default` has to be the zero cost.
+pub trait Cost: Copy + Ord + Add<Output = Self> + Default {}
+
+impl<T: Copy + Ord + Add<Output = T> + Default> Cost for T {}
+
+/// Find a shortest path in an unweighted graph. The path includes `start` and the goal.
+pub fn bfs<S, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<Vec<S>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut parents: FastMap<S, Option<S>> = FastMap::from_iter([(start.clone(), None)]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(state) = queue.pop_front() {
+        if is_goal(&state) {
+            return Some(reconstruct(&parents, state));
+        }
+        for next in successors(&state) {
+            if !parents.contains_key(&next) {
+                parents.insert(next.clone(), Some(state.clone()));
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Number of steps from `start` to every reachable state in an unweighted graph.
+pub fn bfs_distances<S, I>(start: S, mut successors: impl FnMut(&S) -> I) -> FastMap<S, usize>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut distances = FastMap::from_iter([(start.clone(), 0)]);
+    let mut queue = VecDeque::from([(start, 0)]);
+
+    while let Some((state, distance)) = queue.pop_front() {
+        for next in successors(&state) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), distance + 1);
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+    distances
+}
+
+/// Find a cheapest path in a graph with non-negative costs. Returns the cost and the path, which includes `start`
+/// and the goal.
+pub fn dijkstra<S, C, I>(
+    start: S,
+    successors: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<(C, Vec<S>)>
+where
+    S: Clone + Eq + Hash,
+    C: Cost,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar(start, successors, |_| C::default(), is_goal)
+}
+
+/// Find a cheapest path like [`dijkstra`], exploring states with a lower `cost + heuristic` first.
+/// The heuristic must never overestimate the remaining cost, or the result might not be optimal.
+pub fn astar<S, C, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<(C, Vec<S>)>
+where
+    S: Clone + Eq + Hash,
+    C: Cost,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut costs: FastMap<S, C> = FastMap::from_iter([(start.clone(), C::default())]);
+    let mut parents: FastMap<S, Option<S>> = FastMap::from_iter([(start.clone(), None)]);
+    let mut frontier = BinaryHeap::from([Entry {
+        priority: heuristic(&start),
+        cost: C::default(),
+        state: start,
+    }]);
+
+    while let Some(Entry { cost, state, .. }) = frontier.pop() {
+        if costs.get(&state).is_some_and(|best| cost > *best) {
+            continue;
+        }
+        if is_goal(&state) {
+            return Some((cost, reconstruct(&parents, state)));
+        }
+        for (next, step_cost) in successors(&state) {
+            let next_cost = cost + step_cost;
+            if costs.get(&next).is_some_and(|best| next_cost >= *best) {
+                continue;
+            }
+            costs.insert(next.clone(), next_cost);
+            parents.insert(next.clone(), Some(state.clone()));
+            frontier.push(Entry {
+                priority: next_cost + heuristic(&next),
+                cost: next_cost,
+                state: next,
+            });
+        }
+    }
+    None
+}
+
+/// Find every cheapest path to the goal. All goal states that can be reached at the optimal cost are kept,
+/// e.g. reaching the end of a maze facing different directions.
+pub fn dijkstra_all<S, C, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<AllPaths<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Cost,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut costs: FastMap<S, C> = FastMap::from_iter([(start.clone(), C::default())]);
+    let mut predecessors: FastMap<S, Vec<S>> = FastMap::default();
+    let mut frontier = BinaryHeap::from([Entry {
+        priority: C::default(),
+        cost: C::default(),
+        state: start.clone(),
+    }]);
+    let mut best = None;
+    let mut goals = vec![];
+
+    while let Some(Entry { cost, state, .. }) = frontier.pop() {
+        if costs.get(&state).is_some_and(|known| cost > *known) {
+            continue;
+        }
+        if best.is_some_and(|best| cost > best) {
+            break;
+        }
+        if is_goal(&state) {
+            best = Some(cost);
+            goals.push(state);
+            continue;
+        }
+        for (next, step_cost) in successors(&state) {
+            let next_cost = cost + step_cost;
+            match costs.get(&next).map(|known| next_cost.cmp(known)) {
+                Some(Ordering::Greater) => continue,
+                Some(Ordering::Equal) => {
+                    predecessors.entry(next).or_default().push(state.clone());
+                }
+                Some(Ordering::Less) | None => {
+                    costs.insert(next.clone(), next_cost);
+                    predecessors.insert(next.clone(), vec![state.clone()]);
+                    frontier.push(Entry {
+                        priority: next_cost,
+                        cost: next_cost,
+                        state: next,
+                    });
+                }
+            }
+        }
+    }
+
+    Some(AllPaths {
+        cost: best?,
+        start,
+        goals,
+        predecessors,
+    })
+}
+
+/// The result of [`dijkstra_all`].
+#[derive(Debug)]
+pub struct AllPaths<S, C> {
+    pub cost: C,
+    pub start: S,
+    /// Goal states reached at the optimal cost, in the order they were found.
+    pub goals: Vec<S>,
+    predecessors: FastMap<S, Vec<S>>,
+}
+
+impl<S: Clone + Eq + Hash, C> AllPaths<S, C> {
+    /// States that lead to `state` on a cheapest path.
+    pub fn predecessors(&self, state: &S) -> &[S] {
+        self.predecessors.get(state).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every state that lies on at least one cheapest path.
+    pub fn states(&self) -> FastSet<S> {
+        let mut seen: FastSet<S> = self.goals.iter().cloned().collect();
+        let mut stack = self.goals.clone();
+        while let Some(state) = stack.pop() {
+            for previous in self.predecessors(&state) {
+                if seen.insert(previous.clone()) {
+                    stack.push(previous.clone());
+                }
+            }
+        }
+        seen
+    }
+
+    /// Every distinct projection of a state on a cheapest path, e.g. the tiles of a maze when the state also
+    /// contains a facing.
+    pub fn tiles<T: Eq + Hash>(&self, project: impl FnMut(&S) -> T) -> FastSet<T> {
+        self.states().iter().map(project).collect()
+    }
+
+    /// Number of distinct cheapest paths from the start to any goal.
+    pub fn count_paths(&self) -> usize {
+        fn count<S: Clone + Eq + Hash, C>(
+            paths: &AllPaths<S, C>,
+            state: &S,
+            memo: &mut FastMap<S, usize>,
+        ) -> usize {
+            if *state == paths.start {
+                return 1;
+            }
+            if let Some(n) = memo.get(state) {
+                return *n;
+            }
+            let n = paths
+                .predecessors(state)
+                .iter()
+                .map(|previous| count(paths, previous, memo))
+                .sum();
+            memo.insert(state.clone(), n);
+            n
+        }
+
+        let mut memo = FastMap::default();
+        self.goals
+            .iter()
+            .map(|goal| count(self, goal, &mut memo))
+            .sum()
+    }
+
+    /// One of the cheapest paths, from the start to the first goal.
+    pub fn path(&self) -> Vec<S> {
+        let mut state = self.goals[0].clone();
+        let mut path = vec![state.clone()];
+        while state != self.start {
+            state = self.predecessors(&state)[0].clone();
+            path.push(state.clone());
+        }
+        path.reverse();
+        path
+    }
+}
+
+fn reconstruct<S: Clone + Eq + Hash>(parents: &FastMap<S, Option<S>>, goal: S) -> Vec<S> {
+    let mut path = vec![goal];
+    while let Some(Some(parent)) = parents.get(path.last().unwrap()) {
+        path.push(parent.clone());
+    }
+    path.reverse();
+    path
+}
+
+/// A frontier entry. Ordered by priority only, reversed so that `BinaryHeap` pops the cheapest entry first.
+struct Entry<S, C> {
+    priority: C,
+    cost: C,
+    state: S,
+}
+
+impl<S, C: Ord> PartialEq for Entry<S, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<S, C: Ord> Eq for Entry<S, C> {}
+
+impl<S, C: Ord> PartialOrd for Entry<S, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S, C: Ord> Ord for Entry<S, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{astar, bfs, bfs_distances, dijkstra, dijkstra_all};
+    use crate::geometry::{Direction, Point};
+    use crate::grid::{Grid, Pos};
+
+    const MAZE: &str = "\
+###############
+#.......#....E#
+#.#.###.#.###.#
+#.....#.#...#.#
+#.###.#####.#.#
+#.#.#.......#.#
+#.#.#####.###.#
+#...........#.#
+###.#.#####.#.#
+#...#.....#.#.#
+#.#.#.###.#.#.#
+#.....#...#.#.#
+#.###.#.#.#.#.#
+#S..#.....#...#
+###############";
+
+    fn open(grid: &Grid<char>, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        grid.neighbours4(pos).filter(|n| grid[*n] != '#')
+    }
+
+    /// Moving forward costs 1, turning costs 1000.
+    fn reindeer(grid: &Grid<char>, (pos, d): &(Pos, Direction)) -> Vec<((Pos, Direction), u32)> {
+        let mut next = vec![
+            ((*pos, d.rotate_left()), 1000),
+            ((*pos, d.rotate_right()), 1000),
+        ];
+        if let Some(forward) = grid.step(*pos, d.delta()).filter(|p| grid[*p] != '#') {
+            next.push(((forward, *d), 1));
+        }
+        next
+    }
+
+    #[test]
+    fn finds_shortest_unweighted_path() {
+        let grid = Grid::parse_chars(MAZE).unwrap();
+        let (start, end) = (grid.find(&'S').unwrap(), grid.find(&'E').unwrap());
+        let path = bfs(start, |p| open(&grid, *p), |p| *p == end).unwrap();
+        assert_eq!(path.first(), Some(&start));
+        assert_eq!(path.last(), Some(&end));
+        assert_eq!(path.len() - 1, 28);
+        assert_eq!(bfs_distances(start, |p| open(&grid, *p))[&end], 28);
+        assert_eq!(bfs(start, |p| open(&grid, *p), |_| false), None);
+    }
+
+    #[test]
+    fn finds_cheapest_path() {
+        let grid = Grid::parse_chars(MAZE).unwrap();
+        let (start, end) = (grid.find(&'S').unwrap(), grid.find(&'E').unwrap());
+        let (cost, path) = dijkstra(
+            (start, Direction::East),
+            |s| reindeer(&grid, s),
+            |s| s.0 == end,
+        )
+        .unwrap();
+        assert_eq!(cost, 7036);
+        assert_eq!(path[0], (start, Direction::East));
+
+        let heuristic = |s: &(Pos, Direction)| {
+            u32::try_from(Point::from(s.0).manhattan(Point::from(end))).unwrap()
+        };
+        let (cost, _) = astar(
+            (start, Direction::East),
+            |s| reindeer(&grid, s),
+            heuristic,
+            |s| s.0 == end,
+        )
+        .unwrap();
+        assert_eq!(cost, 7036);
+    }
+
+    #[test]
+    fn finds_all_cheapest_paths() {
+        let grid = Grid::parse_chars(MAZE).unwrap();
+        let (start, end) = (grid.find(&'S').unwrap(), grid.find(&'E').unwrap());
+        let paths = dijkstra_all(
+            (start, Direction::East),
+            |s| reindeer(&grid, s),
+            |s| s.0 == end,
+        )
+        .unwrap();
+        assert_eq!(paths.cost, 7036);
+        assert_eq!(paths.tiles(|s| s.0).len(), 45);
+        assert_eq!(paths.count_paths(), 3);
+        let path = paths.path();
+        assert_eq!(path.first(), Some(&(start, Direction::East)));
+        assert_eq!(path.last().map(|s| s.0), Some(end));
+    }
+
+    #[test]
+    fn counts_paths_in_a_diamond() {
+        // 0 -> 1 -> 3 and 0 -> 2 -> 3, both with cost 2.
+        let edges = |n: &u8| match n {
+            0 => vec![(1, 1), (2, 1)],
+            1 | 2 => vec![(3, 1)],
+            _ => vec![],
+        };
+        let paths = dijkstra_all(0_u8, edges, |n| *n == 3).unwrap();
+        assert_eq!(paths.cost, 2_u32);
+        assert_eq!(paths.count_paths(), 2);
+        let mut predecessors = paths.predecessors(&3).to_vec();
+        predecessors.sort_unstable();
+        assert_eq!(predecessors, [1, 2]);
+        assert!(dijkstra_all(0_u8, edges, |n| *n == 4).is_none());
+    }
+}
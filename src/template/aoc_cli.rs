@@ -1,6 +1,8 @@
 /// Wrapper module around the "aoc-cli" command-line.
 use std::{
+    env,
     fmt::Display,
+    path::PathBuf,
     process::{Command, Output, Stdio},
 };
 
@@ -80,15 +82,22 @@ pub fn submit(day: Day, part: u8, result: &str) -> Result<Output, AocCommandErro
     call_aoc_cli(&args)
 }
 
+/// The session file from the aoc-cli setup, `~/.adventofcode.session`. It is passed to every aoc-cli call, so the
+/// template and aoc-cli always use the same session.
+pub fn session_file() -> Option<PathBuf> {
+    let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"))?;
+    Some(PathBuf::from(home).join(".adventofcode.session"))
+}
+
 fn get_input_path(day: Day) -> String {
     format!("data/inputs/{day}.txt")
 }
 
-fn get_puzzle_path(day: Day) -> String {
+pub fn get_puzzle_path(day: Day) -> String {
     format!("data/puzzles/{day}.md")
 }
 
-fn get_year() -> Option<u16> {
+pub fn get_year() -> Option<u16> {
     match std::env::var("AOC_YEAR") {
         Ok(x) => x.parse().ok().or(None),
         Err(_) => None,
@@ -98,6 +107,11 @@ fn get_year() -> Option<u16> {
 fn build_args(command: &str, args: &[String], day: Day) -> Vec<String> {
     let mut cmd_args = args.to_vec();
 
+    if let Some(session_file) = session_file() {
+        cmd_args.push("--session-file".into());
+        cmd_args.push(session_file.display().to_string());
+    }
+
     if let Some(year) = get_year() {
         cmd_args.push("--year".into());
         cmd_args.push(year.to_string());
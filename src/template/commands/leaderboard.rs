@@ -0,0 +1,87 @@
+use std::{path::Path, process};
+
+use crate::template::leaderboard::{format_solve_time, Leaderboard};
+use crate::template::{all_days, ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
+
+pub fn handle(source: &str) {
+    let path = Path::new(source);
+
+    let leaderboard = if path.exists() {
+        Leaderboard::read_from_file(path)
+    } else if let Ok(id) = source.parse::<u64>() {
+        Leaderboard::fetch(id)
+    } else {
+        eprintln!(
+            "Expected a path to a leaderboard JSON file or a leaderboard id, got `{source}`."
+        );
+        process::exit(1);
+    };
+
+    let leaderboard = match leaderboard {
+        Ok(leaderboard) => leaderboard,
+        Err(e) => {
+            eprintln!("failed to load leaderboard: {e}");
+            process::exit(1);
+        }
+    };
+
+    print_ranking(&leaderboard);
+    println!();
+    print_completion_times(&leaderboard);
+}
+
+fn print_ranking(leaderboard: &Leaderboard) {
+    println!(
+        "{ANSI_BOLD}Private leaderboard #{} ({}){ANSI_RESET}",
+        leaderboard.owner_id, leaderboard.event
+    );
+    println!();
+
+    let tens: String = all_days()
+        .map(|day| match day.into_inner() / 10 {
+            0 => ' ',
+            x => char::from(b'0' + x),
+        })
+        .collect();
+    let ones: String = all_days()
+        .map(|day| char::from(b'0' + day.into_inner() % 10))
+        .collect();
+    println!("{:>10} {tens}", "");
+    println!("{:>10} {ones}", "");
+
+    for (index, member) in leaderboard.members.iter().enumerate() {
+        let stars: String = all_days()
+            .map(|day| match member.stars_on(day) {
+                0 => '·',
+                1 => '☆',
+                _ => '★',
+            })
+            .collect();
+        println!(
+            "{:>3}) {:>5} {stars} {ANSI_BOLD}{}{ANSI_RESET} {ANSI_ITALIC}({} stars){ANSI_RESET}",
+            index + 1,
+            member.local_score,
+            member.display_name(),
+            member.stars
+        );
+    }
+}
+
+fn print_completion_times(leaderboard: &Leaderboard) {
+    for member in leaderboard.members.iter().filter(|m| m.stars > 0) {
+        println!("{ANSI_BOLD}{}{ANSI_RESET}", member.display_name());
+
+        for day in all_days().filter(|day| member.stars_on(*day) > 0) {
+            let times: Vec<String> = [1, 2]
+                .iter()
+                .map(|part| {
+                    member
+                        .completion(day, *part)
+                        .and_then(|c| leaderboard.solve_seconds(c))
+                        .map_or_else(|| "-".into(), format_solve_time)
+                })
+                .collect();
+            println!("  Day {day}  {:>8}  {:>8}", times[0], times[1]);
+        }
+    }
+}
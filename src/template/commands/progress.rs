@@ -0,0 +1,42 @@
+use crate::template::readme_stars::{self, Progress};
+use crate::template::{aoc_cli, ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
+
+const DAYS_PER_ROW: usize = 5;
+
+pub fn handle() {
+    let progress = Progress::read_from_puzzles();
+
+    match aoc_cli::get_year() {
+        Some(year) => println!("{ANSI_BOLD}Advent of Code {year}{ANSI_RESET}"),
+        None => println!("{ANSI_BOLD}Advent of Code{ANSI_RESET}"),
+    }
+    println!();
+
+    for row in progress.data.chunks(DAYS_PER_ROW) {
+        let cells: Vec<String> = row
+            .iter()
+            .map(|entry| {
+                let stars = match entry.stars {
+                    0 => format!("{ANSI_ITALIC}··{ANSI_RESET}"),
+                    1 => format!("{ANSI_BOLD}★{ANSI_RESET}{ANSI_ITALIC}·{ANSI_RESET}"),
+                    _ => format!("{ANSI_BOLD}★★{ANSI_RESET}"),
+                };
+                format!("{} {stars}", entry.day)
+            })
+            .collect();
+        println!("{}", cells.join("   "));
+    }
+
+    let total_stars = progress.total_stars();
+    println!("\n{ANSI_BOLD}Total:{ANSI_RESET} {ANSI_ITALIC}{total_stars}/50 stars{ANSI_RESET}");
+
+    println!();
+    match readme_stars::update(&progress) {
+        Ok(()) => {
+            println!("Stored updated progress.");
+        }
+        Err(_) => {
+            eprintln!("Failed to store updated progress.");
+        }
+    }
+}
@@ -2,22 +2,75 @@ use std::process::{Command, Stdio};
 
 use crate::template::Day;
 
-pub fn handle(day: Day, release: bool, dhat: bool, submit_part: Option<u8>) {
+/// Visualization flags that are passed on to the solution, see [`crate::viz`].
+pub struct VizOptions {
+    pub terminal: bool,
+    pub fps: Option<u32>,
+    pub out: Option<String>,
+    pub format: Option<String>,
+}
+
+impl VizOptions {
+    fn args(&self) -> Vec<String> {
+        let mut args = vec![];
+        if self.terminal {
+            args.push("--viz".to_string());
+        }
+        if let Some(fps) = self.fps {
+            args.extend(["--viz-fps".to_string(), fps.to_string()]);
+        }
+        if let Some(out) = &self.out {
+            args.extend(["--viz-out".to_string(), out.clone()]);
+        }
+        if let Some(format) = &self.format {
+            args.extend(["--viz-format".to_string(), format.clone()]);
+        }
+        args
+    }
+}
+
+pub fn handle(
+    day: Day,
+    release: bool,
+    dhat: bool,
+    verbose: bool,
+    viz: &VizOptions,
+    threads: Option<usize>,
+    submit_part: Option<u8>,
+) {
     let mut cmd_args = vec!["run".to_string(), "--bin".to_string(), day.to_string()];
 
+    let mut features = vec![];
+
     if dhat {
-        cmd_args.extend([
-            "--profile".to_string(),
-            "dhat".to_string(),
-            "--features".to_string(),
-            "dhat-heap".to_string(),
-        ]);
+        cmd_args.extend(["--profile".to_string(), "dhat".to_string()]);
+        features.push("dhat-heap");
     } else if release {
         cmd_args.push("--release".to_string());
     }
 
+    // debug output is compiled out of optimized builds unless the feature is enabled.
+    if verbose && (dhat || release) {
+        features.push("verbose");
+    }
+
+    if !features.is_empty() {
+        cmd_args.extend(["--features".to_string(), features.join(",")]);
+    }
+
     cmd_args.push("--".to_string());
 
+    if verbose {
+        cmd_args.push("--verbose".to_string());
+    }
+
+    cmd_args.extend(viz.args());
+
+    if let Some(threads) = threads {
+        cmd_args.push("--threads".to_string());
+        cmd_args.push(threads.to_string());
+    }
+
     if let Some(submit_part) = submit_part {
         cmd_args.push("--submit".to_string());
         cmd_args.push(submit_part.to_string());
@@ -30,5 +83,14 @@ pub fn handle(day: Day, release: bool, dhat: bool, submit_part: Option<u8>) {
         .spawn()
         .unwrap();
 
-    cmd.wait().unwrap();
+    let status = match cmd.wait() {
+        Ok(status) => status,
+        Err(e) => {
+            eprintln!("failed to wait for cargo: {e}");
+            std::process::exit(1);
+        }
+    };
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
 }
@@ -2,9 +2,15 @@ use std::collections::HashSet;
 
 use crate::template::run_multi::run_multi;
 use crate::template::timings::Timings;
-use crate::template::{all_days, readme_benchmarks, Day};
+use crate::template::{all_days, readme_benchmarks, Day, ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
 
-pub fn handle(day: Option<Day>, run_all: bool, store: bool) {
+pub fn handle(
+    day: Option<Day>,
+    run_all: bool,
+    store: bool,
+    compare_hash: bool,
+    threads: Option<usize>,
+) {
     let stored_timings = Timings::read_from_file();
 
     let days_to_run = day.map_or_else(
@@ -21,7 +27,13 @@ pub fn handle(day: Option<Day>, run_all: bool, store: bool) {
         |day| HashSet::from([day]),
     );
 
-    let timings = run_multi(&days_to_run, true, true).unwrap();
+    let timings = run_multi(&days_to_run, true, true, &[], threads).unwrap();
+
+    if compare_hash {
+        println!("\n{ANSI_BOLD}Benchmarking again with the standard hasher{ANSI_RESET}\n");
+        let std_timings = run_multi(&days_to_run, true, true, &["std-hash"], threads).unwrap();
+        print_hash_comparison(&timings, &std_timings);
+    }
 
     if store {
         let merged_timings = stored_timings.merge(&timings);
@@ -38,3 +50,24 @@ pub fn handle(day: Option<Day>, run_all: bool, store: bool) {
         }
     }
 }
+
+fn print_hash_comparison(timings: &Timings, std_timings: &Timings) {
+    println!("\n{ANSI_BOLD}FastMap hasher vs. std hasher{ANSI_RESET}");
+    println!("------");
+    for comparison in timings.compare(std_timings) {
+        let change = comparison
+            .change_percent()
+            .map_or_else(|| "-".to_string(), |change| format!("{change:+.1}%"));
+        println!(
+            "Day {}: {:>10.3}ms vs. {:>10.3}ms {ANSI_ITALIC}({change}){ANSI_RESET}",
+            comparison.day,
+            comparison.nanos / 1_000_000_f64,
+            comparison.baseline_nanos / 1_000_000_f64,
+        );
+    }
+    println!(
+        "\n{ANSI_BOLD}Total:{ANSI_RESET} {:.2}ms vs. {:.2}ms",
+        timings.total_millis(),
+        std_timings.total_millis()
+    );
+}
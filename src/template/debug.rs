@@ -0,0 +1,63 @@
+/// Debug output for solutions that stays out of the way of the runner.
+///
+/// Output is only compiled in for debug builds or when the `verbose` feature is enabled, and only printed
+/// when the solution is run with `--verbose`. The runner turns it off while benching.
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Whether `aoc_debug!` output is compiled into this build.
+pub const COMPILED: bool = cfg!(any(debug_assertions, feature = "verbose"));
+
+/// Whether `aoc_debug!` output is currently printed.
+#[inline]
+#[must_use]
+pub fn enabled() -> bool {
+    COMPILED && ENABLED.load(Ordering::Relaxed)
+}
+
+/// Turns `aoc_debug!` output on or off. Has no effect if debug output is not compiled in.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Prints to stderr if debug output is enabled, see [`enabled`].
+///
+/// The arguments are not evaluated when debug output is disabled.
+#[macro_export]
+macro_rules! aoc_debug {
+    ($($arg:tt)*) => {
+        if $crate::template::debug::enabled() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(all(test, feature = "test_lib"))]
+mod tests {
+    use super::{enabled, set_enabled, COMPILED};
+
+    #[test]
+    fn toggles_output() {
+        assert!(!enabled());
+        set_enabled(true);
+        assert_eq!(enabled(), COMPILED);
+
+        let mut evaluated = false;
+        crate::aoc_debug!("{}", {
+            evaluated = true;
+            "debug"
+        });
+        assert_eq!(evaluated, COMPILED);
+
+        set_enabled(false);
+        evaluated = false;
+        crate::aoc_debug!("{}", {
+            evaluated = true;
+            "debug"
+        });
+        assert!(!evaluated);
+    }
+}
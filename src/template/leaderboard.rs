@@ -0,0 +1,396 @@
+/// Module that reads private leaderboards in the JSON format exported by the Advent of Code website.
+use std::{
+    collections::HashMap,
+    fs,
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+    str::FromStr,
+};
+use tinyjson::JsonValue;
+
+use crate::template::{aoc_cli, Day};
+
+/// Puzzles unlock at midnight EST, which is 05:00 UTC.
+const UNLOCK_HOUR_UTC: u64 = 5;
+
+#[derive(Debug)]
+pub enum Error {
+    SessionNotFound,
+    YearNotSet,
+    CommandNotCallable,
+    BadExitStatus(String),
+    Parser(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::SessionNotFound => write!(
+                f,
+                "no aoc-cli session file found, see the aoc-cli integration in the readme."
+            ),
+            Error::YearNotSet => write!(f, "`AOC_YEAR` is not set."),
+            Error::CommandNotCallable => write!(f, "curl could not be called."),
+            Error::BadExitStatus(stderr) => {
+                write!(f, "curl exited with a non-zero status: {stderr}")
+            }
+            Error::Parser(e) => write!(f, "could not parse leaderboard: {e}"),
+        }
+    }
+}
+
+/// A single star collected by a member.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Completion {
+    pub day: Day,
+    pub part: u8,
+    pub timestamp: u64,
+}
+
+/// A member of a private leaderboard.
+#[derive(Clone, Debug)]
+pub struct Member {
+    pub id: u64,
+    pub name: Option<String>,
+    pub local_score: u64,
+    pub stars: u64,
+    pub last_star_ts: u64,
+    pub completions: Vec<Completion>,
+}
+
+impl Member {
+    /// The member's name, or the placeholder the website shows for anonymous users.
+    pub fn display_name(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("(anonymous user #{})", self.id))
+    }
+
+    /// Number of stars collected on a given day.
+    pub fn stars_on(&self, day: Day) -> usize {
+        self.completions.iter().filter(|c| c.day == day).count()
+    }
+
+    /// Time of completion for a part, if the star was collected.
+    pub fn completion(&self, day: Day, part: u8) -> Option<&Completion> {
+        self.completions
+            .iter()
+            .find(|c| c.day == day && c.part == part)
+    }
+}
+
+/// A private leaderboard. Members are sorted by rank.
+#[derive(Clone, Debug)]
+pub struct Leaderboard {
+    pub event: String,
+    pub owner_id: u64,
+    pub members: Vec<Member>,
+}
+
+impl Leaderboard {
+    /// Read a leaderboard from a saved JSON export.
+    pub fn read_from_file(path: &Path) -> Result<Self, Error> {
+        fs::read_to_string(path)
+            .map_err(|e| Error::Parser(e.to_string()))
+            .and_then(|json| Leaderboard::try_from(json).map_err(Error::Parser))
+    }
+
+    /// Fetch the JSON export of a leaderboard from the website with the aoc-cli session file.
+    ///
+    /// aoc-cli only prints leaderboards as a table without completion times, so the export is fetched with curl.
+    /// The cookie is written to curl's stdin, so it doesn't show up in the process list.
+    /// Please note that the website asks to not request a leaderboard more often than once every 15 minutes.
+    pub fn fetch(id: u64) -> Result<Self, Error> {
+        let session = aoc_cli::session_file()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .ok_or(Error::SessionNotFound)?;
+        let year = aoc_cli::get_year().ok_or(Error::YearNotSet)?;
+        let url = format!("https://adventofcode.com/{year}/leaderboard/private/view/{id}.json");
+
+        let mut cmd = Command::new("curl")
+            .args(["--silent", "--show-error", "--fail", "--config", "-"])
+            .arg(&url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|_| Error::CommandNotCallable)?;
+
+        let config = format!("cookie = \"session={}\"\n", session.trim());
+        cmd.stdin
+            .take()
+            .ok_or(Error::CommandNotCallable)?
+            .write_all(config.as_bytes())
+            .map_err(|_| Error::CommandNotCallable)?;
+
+        let output = cmd
+            .wait_with_output()
+            .map_err(|_| Error::CommandNotCallable)?;
+        if !output.status.success() {
+            return Err(Error::BadExitStatus(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+
+        Leaderboard::try_from(String::from_utf8_lossy(&output.stdout).into_owned())
+            .map_err(Error::Parser)
+    }
+
+    /// Seconds from the puzzle unlock until a star was collected.
+    pub fn solve_seconds(&self, completion: &Completion) -> Option<u64> {
+        let year: i64 = self.event.parse().ok()?;
+        let unlock = unlock_timestamp(year, completion.day)?;
+        completion.timestamp.checked_sub(unlock)
+    }
+}
+
+/// Unix timestamp at which the puzzle for `day` of `year` unlocks.
+#[must_use]
+pub fn unlock_timestamp(year: i64, day: Day) -> Option<u64> {
+    let days = days_from_civil(year, 12, i64::from(day.into_inner()));
+    u64::try_from(days * 86_400)
+        .ok()
+        .map(|x| x + UNLOCK_HOUR_UTC * 3600)
+}
+
+/// Days since the unix epoch for a date in the proleptic gregorian calendar.
+/// see: http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Format a solve time the way the website does, e.g. `00:12:34`. Times above a day are shown as `>24h`.
+#[must_use]
+pub fn format_solve_time(seconds: u64) -> String {
+    if seconds >= 86_400 {
+        return ">24h".into();
+    }
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        (seconds / 60) % 60,
+        seconds % 60
+    )
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl TryFrom<String> for Leaderboard {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let json = JsonValue::from_str(&value).or(Err("not valid JSON file."))?;
+
+        let json = json
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("expected JSON document to be an object.")?;
+
+        let event = json
+            .get("event")
+            .and_then(|v| v.get::<String>())
+            .ok_or("expected JSON document to have string key `event`.")?;
+
+        let owner_id = json
+            .get("owner_id")
+            .and_then(|v| v.get::<f64>().copied())
+            .ok_or("expected JSON document to have numeric key `owner_id`.")?;
+
+        let json_members = json
+            .get("members")
+            .ok_or("expected JSON document to have key `members`.")?
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("expected `json.members` to be an object.")?;
+
+        let mut members = json_members
+            .values()
+            .map(Member::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // NOTE: ties are broken in favor of the member who reached the score first, same as on the website.
+        members.sort_by(|a, b| {
+            b.local_score
+                .cmp(&a.local_score)
+                .then(b.stars.cmp(&a.stars))
+                .then(a.last_star_ts.cmp(&b.last_star_ts))
+                .then(a.id.cmp(&b.id))
+        });
+
+        Ok(Leaderboard {
+            event: event.clone(),
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            owner_id: owner_id as u64,
+            members,
+        })
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn get_u64(json: &HashMap<String, JsonValue>, key: &str) -> Option<u64> {
+    json.get(key)
+        .and_then(|v| v.get::<f64>())
+        .map(|x| *x as u64)
+}
+
+impl TryFrom<&JsonValue> for Member {
+    type Error = String;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let json = value
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("Expected member to be a JSON object.")?;
+
+        let id = get_u64(json, "id").ok_or("Expected member.id to be a number.")?;
+
+        let name = json
+            .get("name")
+            .map(|v| if v.is_null() { None } else { v.get::<String>() })
+            .ok_or("Expected member.name to be null or string.")?;
+
+        let local_score =
+            get_u64(json, "local_score").ok_or("Expected member.local_score to be a number.")?;
+        let stars = get_u64(json, "stars").ok_or("Expected member.stars to be a number.")?;
+        let last_star_ts = get_u64(json, "last_star_ts").unwrap_or(0);
+
+        let days = json
+            .get("completion_day_level")
+            .and_then(|v| v.get::<HashMap<String, JsonValue>>())
+            .ok_or("Expected member.completion_day_level to be an object.")?;
+
+        let mut completions = vec![];
+        for (day, parts) in days {
+            let day = Day::from_str(day).map_err(|e| e.to_string())?;
+            let parts = parts
+                .get::<HashMap<String, JsonValue>>()
+                .ok_or("Expected completion_day_level entries to be objects.")?;
+            for (part, star) in parts {
+                let part = part
+                    .parse()
+                    .map_err(|_| format!("Expected part number, got `{part}`."))?;
+                let timestamp = star
+                    .get::<HashMap<String, JsonValue>>()
+                    .and_then(|star| get_u64(star, "get_star_ts"))
+                    .ok_or("Expected star.get_star_ts to be a number.")?;
+                completions.push(Completion {
+                    day,
+                    part,
+                    timestamp,
+                });
+            }
+        }
+        completions.sort_unstable_by_key(|c| (c.day, c.part));
+
+        Ok(Member {
+            id,
+            name: name.cloned(),
+            local_score,
+            stars,
+            last_star_ts,
+            completions,
+        })
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(all(test, feature = "test_lib"))]
+mod tests {
+    use super::{format_solve_time, unlock_timestamp, Leaderboard};
+    use crate::day;
+
+    fn get_mock_leaderboard() -> Leaderboard {
+        let json = r#"{
+            "owner_id": 1,
+            "event": "2024",
+            "members": {
+                "1": {
+                    "id": 1, "name": "alice", "stars": 3, "local_score": 10, "global_score": 0,
+                    "last_star_ts": 1733118000,
+                    "completion_day_level": {
+                        "1": { "1": { "get_star_ts": 1733029500, "star_index": 1 }, "2": { "get_star_ts": 1733030100, "star_index": 2 } },
+                        "2": { "1": { "get_star_ts": 1733118000, "star_index": 3 } }
+                    }
+                },
+                "2": {
+                    "id": 2, "name": null, "stars": 4, "local_score": 12, "global_score": 0,
+                    "last_star_ts": 1733200000,
+                    "completion_day_level": {
+                        "1": { "1": { "get_star_ts": 1733029300, "star_index": 4 }, "2": { "get_star_ts": 1733029400, "star_index": 5 } },
+                        "2": { "1": { "get_star_ts": 1733115700, "star_index": 6 }, "2": { "get_star_ts": 1733200000, "star_index": 7 } }
+                    }
+                },
+                "3": {
+                    "id": 3, "name": "carol", "stars": 0, "local_score": 0, "global_score": 0,
+                    "last_star_ts": 0,
+                    "completion_day_level": {}
+                }
+            }
+        }"#;
+        Leaderboard::try_from(json.to_string()).unwrap()
+    }
+
+    #[test]
+    fn parses_leaderboard() {
+        let leaderboard = get_mock_leaderboard();
+        assert_eq!(leaderboard.event, "2024");
+        assert_eq!(leaderboard.owner_id, 1);
+        assert_eq!(leaderboard.members.len(), 3);
+    }
+
+    #[test]
+    fn sorts_members_by_score() {
+        let leaderboard = get_mock_leaderboard();
+        let ids: Vec<u64> = leaderboard.members.iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec![2, 1, 3]);
+    }
+
+    #[test]
+    fn handles_anonymous_members() {
+        let leaderboard = get_mock_leaderboard();
+        assert_eq!(leaderboard.members[0].display_name(), "(anonymous user #2)");
+        assert_eq!(leaderboard.members[1].display_name(), "alice");
+    }
+
+    #[test]
+    fn counts_stars_per_day() {
+        let alice = &get_mock_leaderboard().members[1];
+        assert_eq!(alice.stars_on(day!(1)), 2);
+        assert_eq!(alice.stars_on(day!(2)), 1);
+        assert_eq!(alice.stars_on(day!(3)), 0);
+    }
+
+    #[test]
+    fn calculates_solve_times() {
+        let leaderboard = get_mock_leaderboard();
+        let alice = &leaderboard.members[1];
+        let completion = alice.completion(day!(1), 2).unwrap();
+        assert_eq!(leaderboard.solve_seconds(completion), Some(900));
+    }
+
+    #[test]
+    fn calculates_unlock_timestamps() {
+        // 2024-12-01T05:00:00Z
+        assert_eq!(unlock_timestamp(2024, day!(1)), Some(1733029200));
+        // 2023-12-25T05:00:00Z
+        assert_eq!(unlock_timestamp(2023, day!(25)), Some(1703480400));
+    }
+
+    #[test]
+    fn formats_solve_times() {
+        assert_eq!(format_solve_time(0), "00:00:00");
+        assert_eq!(format_solve_time(3723), "01:02:03");
+        assert_eq!(format_solve_time(86_400), ">24h");
+    }
+
+    #[test]
+    #[should_panic]
+    fn panics_for_malformed_leaderboard() {
+        let json = r#"{ "event": "2024", "members": {} }"#.to_string();
+        Leaderboard::try_from(json).unwrap();
+    }
+}
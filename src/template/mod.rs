@@ -2,17 +2,22 @@ use std::{env, fs};
 
 pub mod aoc_cli;
 pub mod commands;
+pub mod debug;
+pub mod leaderboard;
+pub mod ocr;
 pub mod runner;
 
 pub use day::*;
 
 mod day;
 mod readme_benchmarks;
+mod readme_stars;
 mod run_multi;
 mod timings;
 
 pub const ANSI_ITALIC: &str = "\x1b[3m";
 pub const ANSI_BOLD: &str = "\x1b[1m";
+pub const ANSI_RED: &str = "\x1b[31m";
 pub const ANSI_RESET: &str = "\x1b[0m";
 
 /// Helper function that reads a text file to a string.
@@ -39,6 +44,7 @@ pub fn read_file_part(folder: &str, day: Day, part: u8) -> String {
 /// Creates the constant `DAY` and sets up the input and runner for each part.
 ///
 /// The optional, second parameter (1 or 2) allows you to only run a single part of the solution.
+/// Parts may return either an `Option` or a `Result`. If a part returns an error, the process exits with a non-zero status.
 #[macro_export]
 macro_rules! solution {
     ($day:expr) => {
@@ -62,7 +68,11 @@ macro_rules! solution {
         fn main() {
             use $crate::template::runner::*;
             let input = $crate::template::read_file("inputs", DAY);
-            $( run_part($func, &input, DAY, $part); )*
+            let mut failed = false;
+            $( failed |= run_part(|input: &str| $crate::part_answer!($func(input)), input.as_str(), DAY, $part).is_err(); )*
+            if failed {
+                std::process::exit(1);
+            }
         }
     };
 }
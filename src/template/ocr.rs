@@ -0,0 +1,268 @@
+/// Recognizes answers that are drawn as ASCII art in the fixed Advent of Code letter font.
+/// Both the small font (6 rows, e.g. 2016 day 8 or 2022 day 10) and the large font (10 rows, e.g. 2018 day 10) are supported.
+const LIT: [char; 2] = ['#', '█'];
+
+const SMALL_HEIGHT: usize = 6;
+const LARGE_HEIGHT: usize = 10;
+
+const SMALL_FONT: [(char, [&str; SMALL_HEIGHT]); 18] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', ["###", ".#.", ".#.", ".#.", ".#.", "###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', ["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+const LARGE_FONT: [(char, [&str; LARGE_HEIGHT]); 15] = [
+    (
+        'A',
+        [
+            "..##..", ".#..#.", "#....#", "#....#", "#....#", "######", "#....#", "#....#",
+            "#....#", "#....#",
+        ],
+    ),
+    (
+        'B',
+        [
+            "#####.", "#....#", "#....#", "#....#", "#####.", "#....#", "#....#", "#....#",
+            "#....#", "#####.",
+        ],
+    ),
+    (
+        'C',
+        [
+            ".####.", "#....#", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....",
+            "#....#", ".####.",
+        ],
+    ),
+    (
+        'E',
+        [
+            "######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....",
+            "#.....", "######",
+        ],
+    ),
+    (
+        'F',
+        [
+            "######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....",
+            "#.....", "#.....",
+        ],
+    ),
+    (
+        'G',
+        [
+            ".####.", "#....#", "#.....", "#.....", "#.....", "#..###", "#....#", "#....#",
+            "#...##", ".###.#",
+        ],
+    ),
+    (
+        'H',
+        [
+            "#....#", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#",
+            "#....#", "#....#",
+        ],
+    ),
+    (
+        'J',
+        [
+            "...###", "....#.", "....#.", "....#.", "....#.", "....#.", "....#.", "#...#.",
+            "#...#.", ".###..",
+        ],
+    ),
+    (
+        'K',
+        [
+            "#....#", "#...#.", "#..#..", "#.#...", "##....", "##....", "#.#...", "#..#..",
+            "#...#.", "#....#",
+        ],
+    ),
+    (
+        'L',
+        [
+            "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....",
+            "#.....", "######",
+        ],
+    ),
+    (
+        'N',
+        [
+            "#....#", "##...#", "##...#", "#.#..#", "#.#..#", "#..#.#", "#..#.#", "#...##",
+            "#...##", "#....#",
+        ],
+    ),
+    (
+        'P',
+        [
+            "#####.", "#....#", "#....#", "#....#", "#####.", "#.....", "#.....", "#.....",
+            "#.....", "#.....",
+        ],
+    ),
+    (
+        'R',
+        [
+            "#####.", "#....#", "#....#", "#....#", "#####.", "#..#..", "#...#.", "#...#.",
+            "#....#", "#....#",
+        ],
+    ),
+    (
+        'X',
+        [
+            "#....#", "#....#", ".#..#.", ".#..#.", "..##..", "..##..", ".#..#.", ".#..#.",
+            "#....#", "#....#",
+        ],
+    ),
+    (
+        'Z',
+        [
+            "######", ".....#", ".....#", "....#.", "...#..", "..#...", ".#....", "#.....",
+            "#.....", "######",
+        ],
+    ),
+];
+
+/// Decode an answer drawn in the Advent of Code letter font.
+/// Any character other than `#` or `█` is treated as an unlit pixel.
+/// Returns `None` if the drawing has an unsupported height or contains an unknown letter.
+#[must_use]
+pub fn parse(drawing: &str) -> Option<String> {
+    let rows: Vec<Vec<bool>> = drawing
+        .lines()
+        .map(|line| line.trim_end().chars().map(|c| LIT.contains(&c)).collect())
+        .skip_while(|row: &Vec<bool>| !row.contains(&true))
+        .collect();
+
+    let height = rows.iter().rposition(|row| row.contains(&true))? + 1;
+    let rows = &rows[..height];
+    let width = rows.iter().map(Vec::len).max()?;
+
+    let is_lit = |x: usize, y: usize| rows[y].get(x).copied().unwrap_or(false);
+    let is_empty_column = |x: usize| (0..height).all(|y| !is_lit(x, y));
+
+    let mut letters = String::new();
+    let mut x = 0;
+
+    while x < width {
+        if is_empty_column(x) {
+            x += 1;
+            continue;
+        }
+
+        let start = x;
+        while x < width && !is_empty_column(x) {
+            x += 1;
+        }
+
+        let glyph: Vec<String> = (0..height)
+            .map(|y| {
+                (start..x)
+                    .map(|x| if is_lit(x, y) { '#' } else { '.' })
+                    .collect()
+            })
+            .collect();
+
+        letters.push(match_glyph(&glyph)?);
+    }
+
+    Some(letters)
+}
+
+fn match_glyph(glyph: &[String]) -> Option<char> {
+    let matches = |rows: &[&str]| rows.iter().copied().eq(glyph.iter().map(String::as_str));
+
+    let letter = match glyph.len() {
+        SMALL_HEIGHT => SMALL_FONT.iter().find(|(_, rows)| matches(rows))?.0,
+        LARGE_HEIGHT => LARGE_FONT.iter().find(|(_, rows)| matches(rows))?.0,
+        _ => return None,
+    };
+
+    Some(letter)
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(all(test, feature = "test_lib"))]
+mod tests {
+    use super::{parse, LARGE_FONT, SMALL_FONT};
+
+    fn render<const N: usize>(font: &[(char, [&str; N])], text: &str, spacing: usize) -> String {
+        (0..N)
+            .map(|y| {
+                text.chars()
+                    .map(|c| {
+                        let (_, rows) = font.iter().find(|(l, _)| *l == c).unwrap();
+                        let width = rows.iter().map(|r| r.len()).max().unwrap();
+                        format!("{:.<width$}{}", rows[y], ".".repeat(spacing))
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn parses_small_font() {
+        let drawing = [
+            "###..#..#.###..####.###..####.#..#.#....",
+            "#..#.#..#.#..#.#....#..#.#....#..#.#....",
+            "#..#.####.#..#.###..#..#.###..####.#....",
+            "###..#..#.###..#....###..#....#..#.#....",
+            "#.#..#..#.#.#..#....#....#....#..#.#....",
+            "#..#.#..#.#..#.####.#....####.#..#.####.",
+        ]
+        .join("\n");
+        assert_eq!(parse(&drawing), Some("RHREPEHL".into()));
+    }
+
+    #[test]
+    fn parses_all_small_letters() {
+        let text: String = SMALL_FONT.iter().map(|(c, _)| c).collect();
+        assert_eq!(parse(&render(&SMALL_FONT, &text, 1)), Some(text));
+    }
+
+    #[test]
+    fn parses_all_large_letters() {
+        let text: String = LARGE_FONT.iter().map(|(c, _)| c).collect();
+        assert_eq!(parse(&render(&LARGE_FONT, &text, 2)), Some(text));
+    }
+
+    #[test]
+    fn parses_block_characters() {
+        let drawing = render(&SMALL_FONT, "HI", 1)
+            .replace('#', "█")
+            .replace('.', " ");
+        assert_eq!(parse(&drawing), Some("HI".into()));
+    }
+
+    #[test]
+    fn ignores_surrounding_blank_lines() {
+        let drawing = format!("\n\n{}\n\n", render(&LARGE_FONT, "XZ", 2));
+        assert_eq!(parse(&drawing), Some("XZ".into()));
+    }
+
+    #[test]
+    fn rejects_unknown_letters() {
+        let drawing = ["#.#", ".#.", "#.#", ".#.", "#.#", ".#."].join("\n");
+        assert_eq!(parse(&drawing), None);
+    }
+
+    #[test]
+    fn rejects_unsupported_heights() {
+        assert_eq!(parse("42"), None);
+        assert_eq!(parse("#\n#\n#"), None);
+        assert_eq!(parse(""), None);
+    }
+}
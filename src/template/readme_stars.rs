@@ -0,0 +1,197 @@
+/// Module that updates the readme with star progress computed from local puzzle descriptions.
+/// The table format matches the one written by the `aoc-readme-stars` action, so both can be used interchangeably.
+use std::fs;
+
+use crate::template::readme_benchmarks::{locate_table, Error};
+use crate::template::{all_days, aoc_cli, Day};
+
+static MARKER: &str = "<!--- advent_readme_stars table --->";
+
+/// Text that `aoc-cli` keeps in the puzzle description for every solved part.
+static ANSWER_MARKER: &str = "Your puzzle answer was";
+
+/// Text that the puzzle description shows once both stars of a day are collected.
+static COMPLETE_MARKER: &str = "Both parts of this puzzle are complete";
+
+/// Represents the number of stars collected for a single day.
+#[derive(Clone, Debug)]
+pub struct DayStars {
+    pub day: Day,
+    pub stars: u8,
+}
+
+/// Represents the star progress for all days of advent.
+#[derive(Clone, Debug, Default)]
+pub struct Progress {
+    pub data: Vec<DayStars>,
+}
+
+impl Progress {
+    /// Read progress from the puzzle descriptions in `data/puzzles`. Missing files count as zero stars.
+    pub fn read_from_puzzles() -> Self {
+        Progress {
+            data: all_days()
+                .map(|day| DayStars {
+                    day,
+                    stars: fs::read_to_string(aoc_cli::get_puzzle_path(day))
+                        .map(|puzzle| count_stars(day, &puzzle))
+                        .unwrap_or(0),
+                })
+                .collect(),
+        }
+    }
+
+    /// Sum up the stars collected over all days.
+    pub fn total_stars(&self) -> u32 {
+        self.data.iter().map(|x| u32::from(x.stars)).sum()
+    }
+}
+
+/// Count the stars recorded in a puzzle description. Each solved part adds an answer paragraph, except for the
+/// second part of day 25, which has no answer and is only marked as complete.
+#[must_use]
+pub fn count_stars(day: Day, puzzle: &str) -> u8 {
+    if day.into_inner() == 25 && puzzle.contains(COMPLETE_MARKER) {
+        return 2;
+    }
+    puzzle.matches(ANSWER_MARKER).count().min(2) as u8
+}
+
+fn construct_table(prefix: &str, progress: &Progress, year: Option<u16>) -> String {
+    let header = match year {
+        Some(year) => format!("{prefix} {year} Results"),
+        None => format!("{prefix} Results"),
+    };
+
+    let mut lines: Vec<String> = vec![
+        MARKER.into(),
+        header,
+        String::new(),
+        "| Day | Part 1 | Part 2 |".into(),
+        "| :---: | :---: | :---: |".into(),
+    ];
+
+    for entry in progress.data.iter().filter(|x| x.stars > 0) {
+        let day = entry.day.into_inner();
+        let link = match year {
+            Some(year) => format!("[Day {day}](https://adventofcode.com/{year}/day/{day})"),
+            None => format!("Day {day}"),
+        };
+        lines.push(format!(
+            "| {} | {} | {} |",
+            link,
+            if entry.stars >= 1 { "⭐" } else { " " },
+            if entry.stars >= 2 { "⭐" } else { " " },
+        ));
+    }
+
+    lines.push(MARKER.into());
+
+    lines.join("\n")
+}
+
+fn update_content(s: &mut String, progress: &Progress, year: Option<u16>) -> Result<(), Error> {
+    let positions = locate_table(s, MARKER)?;
+    let table = construct_table("##", progress, year);
+    s.replace_range(positions.pos_start..positions.pos_end, &table);
+    Ok(())
+}
+
+pub fn update(progress: &Progress) -> Result<(), Error> {
+    let path = "README.md";
+    let mut readme = String::from_utf8_lossy(&fs::read(path)?).to_string();
+    update_content(&mut readme, progress, aoc_cli::get_year())?;
+    fs::write(path, &readme)?;
+    Ok(())
+}
+
+#[cfg(all(test, feature = "test_lib"))]
+mod tests {
+    use super::{count_stars, update_content, DayStars, Progress, MARKER};
+    use crate::day;
+
+    fn get_mock_progress() -> Progress {
+        Progress {
+            data: vec![
+                DayStars {
+                    day: day!(1),
+                    stars: 2,
+                },
+                DayStars {
+                    day: day!(2),
+                    stars: 0,
+                },
+                DayStars {
+                    day: day!(3),
+                    stars: 1,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn counts_answers_in_puzzle() {
+        assert_eq!(count_stars(day!(1), "## --- Day 1 ---\nfoo"), 0);
+        assert_eq!(
+            count_stars(day!(1), "Your puzzle answer was `1`.\n## --- Part Two ---"),
+            1
+        );
+        assert_eq!(
+            count_stars(
+                day!(1),
+                "Your puzzle answer was `1`.\nYour puzzle answer was `2`."
+            ),
+            2
+        );
+    }
+
+    #[test]
+    fn counts_both_stars_of_completed_day_25() {
+        let first_star = "Your puzzle answer was `1`.\n## --- Part Two ---";
+        assert_eq!(count_stars(day!(25), first_star), 1);
+        let complete = "Your puzzle answer was `1`.\nBoth parts of this puzzle are complete! They provide two gold stars: **";
+        assert_eq!(count_stars(day!(25), complete), 2);
+        assert_eq!(count_stars(day!(24), complete), 1);
+    }
+
+    #[test]
+    fn sums_total_stars() {
+        assert_eq!(get_mock_progress().total_stars(), 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn errors_if_marker_not_present() {
+        let mut s = "# readme".to_string();
+        update_content(&mut s, &get_mock_progress(), Some(2024)).unwrap();
+    }
+
+    #[test]
+    fn updates_existing_stars() {
+        let mut s = format!("foo\n{}\nbar", MARKER);
+        update_content(&mut s, &get_mock_progress(), Some(2024)).unwrap();
+        update_content(&mut s, &get_mock_progress(), Some(2024)).unwrap();
+        assert_eq!(s.matches(MARKER).collect::<Vec<&str>>().len(), 2);
+        assert_eq!(s.matches("## 2024 Results").collect::<Vec<&str>>().len(), 1);
+    }
+
+    #[test]
+    fn format_stars() {
+        let mut s = format!("foo\n{}\nbar", MARKER);
+        update_content(&mut s, &get_mock_progress(), Some(2024)).unwrap();
+        let expected = [
+            "foo",
+            "<!--- advent_readme_stars table --->",
+            "## 2024 Results",
+            "",
+            "| Day | Part 1 | Part 2 |",
+            "| :---: | :---: | :---: |",
+            "| [Day 1](https://adventofcode.com/2024/day/1) | ⭐ | ⭐ |",
+            "| [Day 3](https://adventofcode.com/2024/day/3) | ⭐ |   |",
+            "<!--- advent_readme_stars table --->",
+            "bar",
+        ]
+        .join("\n");
+        assert_eq!(s, expected);
+    }
+}
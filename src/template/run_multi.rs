@@ -1,14 +1,23 @@
 use std::{collections::HashSet, io};
 
-use crate::template::{Day, ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
+use crate::template::{Day, ANSI_BOLD, ANSI_ITALIC, ANSI_RED, ANSI_RESET};
 
 use super::{
     all_days,
     timings::{Timing, Timings},
 };
 
-pub fn run_multi(days_to_run: &HashSet<Day>, is_release: bool, is_timed: bool) -> Option<Timings> {
+/// Run the solutions of `days_to_run`, building them with the cargo `features` in addition to the defaults.
+/// `threads` is passed on to the solutions as `--threads`, see [`crate::par`].
+pub fn run_multi(
+    days_to_run: &HashSet<Day>,
+    is_release: bool,
+    is_timed: bool,
+    features: &[&str],
+    threads: Option<usize>,
+) -> Option<Timings> {
     let mut timings: Vec<Timing> = Vec::with_capacity(days_to_run.len());
+    let mut failed_days: Vec<Day> = vec![];
 
     let mut need_space = false;
 
@@ -24,16 +33,33 @@ pub fn run_multi(days_to_run: &HashSet<Day>, is_release: bool, is_timed: bool) -
             println!("{ANSI_BOLD}Day {day}{ANSI_RESET}");
             println!("------");
 
-            let output = child_commands::run_solution(day, is_timed, is_release).unwrap();
+            let output =
+                child_commands::run_solution(day, is_timed, is_release, features, threads).unwrap();
 
-            if output.is_empty() {
-                println!("Not solved.");
-            } else {
-                let val = child_commands::parse_exec_time(&output, day);
+            if output.failed {
+                println!("{ANSI_RED}Failed.{ANSI_RESET}");
+                failed_days.push(day);
+            }
+
+            if output.lines.is_empty() {
+                if !output.failed {
+                    println!("Not solved.");
+                }
+            } else if !output.failed {
+                // the timings of a day with a failed part are incomplete and are not stored.
+                let val = child_commands::parse_exec_time(&output.lines, day);
                 timings.push(val);
             }
         });
 
+    if !failed_days.is_empty() {
+        let days: Vec<String> = failed_days.iter().map(|day| format!("Day {day}")).collect();
+        println!(
+            "\n{ANSI_BOLD}Failed:{ANSI_RESET} {ANSI_RED}{}{ANSI_RESET}",
+            days.join(", ")
+        );
+    }
+
     if is_timed {
         let timings = Timings { data: timings };
         let total_millis = timings.total_millis();
@@ -64,10 +90,18 @@ pub fn get_path_for_bin(day: Day) -> String {
     format!("./src/bin/{day}.rs")
 }
 
+/// Output of a solution binary.
+pub struct SolutionOutput {
+    /// Lines written to stdout.
+    pub lines: Vec<String>,
+    /// Whether the binary exited with a non-zero status, e.g. because a part returned an error.
+    pub failed: bool,
+}
+
 /// All solutions live in isolated binaries.
 /// This module encapsulates interaction with these binaries, both invoking them as well as parsing the timing output.
 pub mod child_commands {
-    use super::{get_path_for_bin, Error};
+    use super::{get_path_for_bin, Error, SolutionOutput};
     use crate::template::Day;
     use std::{
         io::{BufRead, BufReader},
@@ -77,10 +111,19 @@ pub mod child_commands {
     };
 
     /// Run the solution bin for a given day
-    pub fn run_solution(day: Day, is_timed: bool, is_release: bool) -> Result<Vec<String>, Error> {
+    pub fn run_solution(
+        day: Day,
+        is_timed: bool,
+        is_release: bool,
+        features: &[&str],
+        threads: Option<usize>,
+    ) -> Result<SolutionOutput, Error> {
         // skip command invocation for days that have not been scaffolded yet.
         if !Path::new(&get_path_for_bin(day)).exists() {
-            return Ok(vec![]);
+            return Ok(SolutionOutput {
+                lines: vec![],
+                failed: false,
+            });
         }
 
         let day_padded = day.to_string();
@@ -90,12 +133,25 @@ pub mod child_commands {
             args.push("--release");
         }
 
+        let features = features.join(",");
+        if !features.is_empty() {
+            args.push("--features");
+            args.push(&features);
+        }
+
+        args.push("--");
+
         if is_timed {
             // mirror `--time` flag to child invocations.
-            args.push("--");
             args.push("--time");
         }
 
+        let threads = threads.map(|threads| threads.to_string());
+        if let Some(threads) = &threads {
+            args.push("--threads");
+            args.push(threads);
+        }
+
         // spawn child command with piped stdout/stderr.
         // forward output to stdout/stderr while grabbing stdout lines.
 
@@ -123,9 +179,12 @@ pub mod child_commands {
         }
 
         thread.join().unwrap();
-        cmd.wait()?;
+        let status = cmd.wait()?;
 
-        Ok(output)
+        Ok(SolutionOutput {
+            lines: output,
+            failed: !status.success(),
+        })
     }
 
     pub fn parse_exec_time(output: &[String], day: Day) -> super::Timing {
@@ -134,6 +193,7 @@ pub mod child_commands {
             part_1: None,
             part_2: None,
             total_nanos: 0_f64,
+            threads: None,
         };
 
         output
@@ -149,9 +209,9 @@ pub mod child_commands {
                 };
 
                 let part = l.split(':').next()?;
-                Some((part, timing_str, nanos))
+                Some((part, timing_str, nanos, parse_threads(l)))
             })
-            .for_each(|(part, timing_str, nanos)| {
+            .for_each(|(part, timing_str, nanos, threads)| {
                 if part.contains("Part 1") {
                     timings.part_1 = Some(timing_str.into());
                 } else if part.contains("Part 2") {
@@ -159,6 +219,7 @@ pub mod child_commands {
                 }
 
                 timings.total_nanos += nanos;
+                timings.threads = timings.threads.max(threads);
             });
 
         timings
@@ -174,7 +235,7 @@ pub mod child_commands {
             .split(" samples)")
             .next()?
             .split('(')
-            .last()?
+            .next_back()?
             .split('@')
             .next()?
             .trim();
@@ -189,6 +250,17 @@ pub mod child_commands {
         Some((str_timing, parsed_timing))
     }
 
+    /// The thread count that the runner appends to parts that ran in parallel, e.g. `(1ms @ 10 samples) [threads: 8]`.
+    fn parse_threads(line: &str) -> Option<usize> {
+        line.rsplit_once(" samples)")?
+            .1
+            .trim()
+            .strip_prefix("[threads: ")?
+            .strip_suffix(']')?
+            .parse()
+            .ok()
+    }
+
     /// copied from: https://github.com/rust-lang/rust/blob/1.64.0/library/std/src/macros.rs#L328-L333
     #[cfg(feature = "test_lib")]
     macro_rules! assert_approx_eq {
@@ -253,5 +325,23 @@ pub mod child_commands {
             assert_eq!(res.part_1.is_none(), true);
             assert_eq!(res.part_2.is_none(), true);
         }
+
+        #[test]
+        fn parses_thread_counts() {
+            let res = parse_exec_time(
+                &[
+                    "Part 1: 41 (10.5µs @ 10000 samples)".into(),
+                    "Part 2: 6 (1.2ms @ 800 samples) [threads: 8]".into(),
+                    "".into(),
+                ],
+                day!(6),
+            );
+            assert_approx_eq!(res.total_nanos, 1210500_f64);
+            assert_eq!(res.part_2.unwrap(), "1.2ms");
+            assert_eq!(res.threads, Some(8));
+
+            let res = parse_exec_time(&["Part 1: 1 (2ms @ 10 samples)".into()], day!(1));
+            assert_eq!(res.threads, None);
+        }
     }
 }
@@ -1,4 +1,6 @@
 /// Encapsulates code that interacts with solution functions.
+use std::convert::Infallible;
+use std::error::Error;
 use std::fmt::Display;
 use std::hint::black_box;
 use std::io::{stdout, Write};
@@ -6,30 +8,176 @@ use std::process::Output;
 use std::time::{Duration, Instant};
 use std::{cmp, env, process};
 
-use crate::template::ANSI_BOLD;
-use crate::template::{aoc_cli, Day, ANSI_ITALIC, ANSI_RESET};
+use crate::template::{aoc_cli, debug, ocr, Day, ANSI_ITALIC, ANSI_RESET};
+use crate::template::{ANSI_BOLD, ANSI_RED};
+use crate::{par, viz};
 
-pub fn run_part<I: Clone, T: Display>(func: impl Fn(I) -> Option<T>, input: I, day: Day, part: u8) {
+/// Values that a solution part may return.
+pub trait PartResult {
+    type Answer: Display;
+    type Error: Display;
+
+    /// Converts the value into the answer. `Ok(None)` means that the part is not solved yet.
+    fn into_answer(self) -> Result<Option<Self::Answer>, Self::Error>;
+}
+
+impl<T: Display> PartResult for Option<T> {
+    type Answer = T;
+    type Error = Infallible;
+
+    fn into_answer(self) -> Result<Option<T>, Infallible> {
+        Ok(self)
+    }
+}
+
+impl<T: Display, E: Display> PartResult for Result<T, E> {
+    type Answer = T;
+    type Error = E;
+
+    fn into_answer(self) -> Result<Option<T>, E> {
+        self.map(Some)
+    }
+}
+
+/// The error of a part, see [`part_answer!`](crate::part_answer). Calling `message` on `&&PartError(&e)` picks
+/// [`ErrorChain`] if the error implements `Error` and [`DisplayMessage`] otherwise.
+pub struct PartError<'a, E>(pub &'a E);
+
+pub trait ErrorChain {
+    /// The error and all of its sources, e.g. `couldn't parse machine: line 3: expected a number`.
+    fn message(&self) -> String;
+}
+
+impl<E: Error> ErrorChain for &PartError<'_, E> {
+    fn message(&self) -> String {
+        let mut message = self.0.to_string();
+        let mut source = self.0.source();
+        while let Some(error) = source {
+            message.push_str(&format!(": {error}"));
+            source = error.source();
+        }
+        message
+    }
+}
+
+pub trait DisplayMessage {
+    /// The error as it is displayed.
+    fn message(&self) -> String;
+}
+
+impl<E: Display> DisplayMessage for PartError<'_, E> {
+    fn message(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+/// Converts the value of a solution part into its answer, with the error formatted for printing. Errors that
+/// implement `Error` are printed with their `source()` chain.
+///
+/// This is a macro, because only the caller knows the concrete error type and whether it implements `Error`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! part_answer {
+    ($result:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::template::runner::{DisplayMessage as _, ErrorChain as _};
+        $crate::template::runner::PartResult::into_answer($result)
+            .map_err(|e| (&&$crate::template::runner::PartError(&e)).message())
+    }};
+}
+
+/// Run and print a solution part. Returns the error message if the part failed.
+pub fn run_part<I: Clone, A: Display>(
+    func: impl Fn(I) -> Result<Option<A>, String>,
+    input: I,
+    day: Day,
+    part: u8,
+) -> Result<(), String> {
     let part_str = format!("Part {part}");
+    let viz_options = start_viz(part);
+    set_threads();
+    par::take_used();
+
+    let (result, duration, samples) = run_timed(func, input, |result| {
+        // visualizations only cover the first execution.
+        let frames = viz::stop();
+        if let Some(viz::Target::Images { dir, .. }) = viz_options.as_ref().map(|o| &o.target) {
+            println!("{part_str}: wrote {frames} frames to {}", dir.display());
+        }
+        print_result(result, &part_str, "");
+    });
+
+    let threads = par::take_used().then(par::threads);
+    print_result(
+        &result,
+        &part_str,
+        &format_duration(&duration, samples, threads),
+    );
+
+    if let Some(result) = result? {
+        // answers drawn in the letter font are submitted as the letters they spell.
+        let answer = result.to_string();
+        let answer = match ocr::parse(&answer) {
+            Some(letters) => {
+                println!("{part_str}: {ANSI_BOLD}{letters}{ANSI_RESET} {ANSI_ITALIC}(decoded){ANSI_RESET}");
+                letters
+            }
+            None => answer,
+        };
+        submit_result(answer, day, part);
+    }
+
+    Ok(())
+}
+
+/// Enable the visualization if any `--viz` flags were passed.
+fn start_viz(part: u8) -> Option<viz::Options> {
+    let args: Vec<String> = env::args().collect();
 
-    let (result, duration, samples) =
-        run_timed(func, input, |result| print_result(result, &part_str, ""));
+    let options = match viz::Options::from_args(&args, &format!("part{part}")) {
+        Ok(options) => options?,
+        Err(e) => {
+            eprintln!("Unexpected command-line input: {e}.");
+            process::exit(1);
+        }
+    };
+
+    if let Err(e) = viz::start(options.clone()) {
+        eprintln!("{e}");
+        process::exit(1);
+    }
 
-    print_result(&result, &part_str, &format_duration(&duration, samples));
+    Some(options)
+}
 
-    if let Some(result) = result {
-        submit_result(result, day, part);
+/// Apply the `--threads` flag, see [`crate::par`].
+fn set_threads() {
+    let args: Vec<String> = env::args().collect();
+    let Some(index) = args.iter().position(|x| x == "--threads") else {
+        return;
+    };
+
+    match args.get(index + 1).map(|x| x.parse::<usize>()) {
+        Some(Ok(threads)) => par::set_threads(threads),
+        _ => {
+            eprintln!("Unexpected command-line input. Format: cargo solve 1 --threads 4");
+            process::exit(1);
+        }
     }
 }
 
 /// Run a solution part. The behavior differs depending on whether we are running a release or debug build:
 ///  1. in debug, the function is executed once.
 ///  2. in release, the function is benched (approx. 1 second of execution time or 10 samples, whatever take longer.)
+///
+/// `aoc_debug!` output is only printed for the first execution if `--verbose` was passed.
 fn run_timed<I: Clone, T>(
     func: impl Fn(I) -> T,
     input: I,
     hook: impl Fn(&T),
 ) -> (T, Duration, u128) {
+    debug::set_enabled(env::args().any(|x| x == "--verbose"));
+
     let timer = Instant::now();
     let result = {
         let input = input.clone();
@@ -41,6 +189,7 @@ fn run_timed<I: Clone, T>(
     };
     let base_time = timer.elapsed();
 
+    debug::set_enabled(false);
     hook(&result);
 
     let run = if std::env::args().any(|x| x == "--time") {
@@ -86,19 +235,21 @@ fn average_duration(numbers: &[Duration]) -> u128 {
         / numbers.len() as u128
 }
 
-fn format_duration(duration: &Duration, samples: u128) -> String {
+/// Parts that used [`crate::par`] get their thread count appended, e.g. ` (1.2ms @ 10 samples) [threads: 8]`.
+fn format_duration(duration: &Duration, samples: u128, threads: Option<usize>) -> String {
+    let threads = threads.map_or_else(String::new, |threads| format!(" [threads: {threads}]"));
     if samples == 1 {
-        format!(" ({duration:.1?})")
+        format!(" ({duration:.1?}){threads}")
     } else {
-        format!(" ({duration:.1?} @ {samples} samples)")
+        format!(" ({duration:.1?} @ {samples} samples){threads}")
     }
 }
 
-fn print_result<T: Display>(result: &Option<T>, part: &str, duration_str: &str) {
+fn print_result<T: Display>(result: &Result<Option<T>, String>, part: &str, duration_str: &str) {
     let is_intermediate_result = duration_str.is_empty();
 
     match result {
-        Some(result) => {
+        Ok(Some(result)) => {
             if result.to_string().contains('\n') {
                 let str = format!("{part}: ▼ {duration_str}");
                 if is_intermediate_result {
@@ -118,7 +269,7 @@ fn print_result<T: Display>(result: &Option<T>, part: &str, duration_str: &str)
                 }
             }
         }
-        None => {
+        Ok(None) => {
             if is_intermediate_result {
                 print!("{part}: ✖");
             } else {
@@ -126,6 +277,22 @@ fn print_result<T: Display>(result: &Option<T>, part: &str, duration_str: &str)
                 println!("{part}: ✖             ");
             }
         }
+        Err(e) => {
+            if is_intermediate_result {
+                print!("{part}: ✖");
+            } else {
+                // NOTE: timings are left out so that failed parts are not stored as benchmarks.
+                let mut lines = e.lines();
+                print!("\r");
+                println!(
+                    "{part}: ✖ {ANSI_RED}{}{ANSI_RESET}",
+                    lines.next().unwrap_or("error")
+                );
+                for line in lines {
+                    println!("  {ANSI_RED}{line}{ANSI_RESET}");
+                }
+            }
+        }
     }
 }
 
@@ -167,3 +334,65 @@ fn submit_result<T: Display>(
     println!("Submitting result via aoc-cli...");
     Some(aoc_cli::submit(day, part, &result.to_string()))
 }
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(all(test, feature = "test_lib"))]
+mod tests {
+    use std::error::Error;
+    use std::fmt::{self, Display};
+
+    use crate::parse::ParseError;
+
+    /// An error that implements `Display` but not `Error`.
+    struct NoPath(u32);
+
+    impl Display for NoPath {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "no path after {} steps", self.0)
+        }
+    }
+
+    #[derive(Debug)]
+    struct MachineError(ParseError);
+
+    impl Display for MachineError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "couldn't parse machine")
+        }
+    }
+
+    impl Error for MachineError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    #[test]
+    fn prints_errors() {
+        let result: Result<u32, _> = Err(NoPath(3));
+        assert_eq!(
+            crate::part_answer!(result),
+            Err("no path after 3 steps".into())
+        );
+
+        let result: Result<u32, String> = Err("no path".into());
+        assert_eq!(crate::part_answer!(result), Err("no path".into()));
+        assert_eq!(crate::part_answer!(Ok::<u32, String>(4)), Ok(Some(4)));
+        assert_eq!(crate::part_answer!(None::<u32>), Ok(None));
+    }
+
+    #[test]
+    fn prints_error_chains() {
+        let parse_error = ParseError {
+            line: 3,
+            column: 1,
+            message: "expected a number".into(),
+        };
+        let result: Result<u32, _> = Err(MachineError(parse_error));
+        assert_eq!(
+            crate::part_answer!(result),
+            Err("couldn't parse machine: line 3, column 1: expected a number".into())
+        );
+    }
+}
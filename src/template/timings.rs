@@ -12,6 +12,8 @@ pub struct Timing {
     pub part_1: Option<String>,
     pub part_2: Option<String>,
     pub total_nanos: f64,
+    /// Thread count of parts that ran in parallel, see [`crate::par`].
+    pub threads: Option<usize>,
 }
 
 /// Represents benchmark times for a set of days.
@@ -51,7 +53,7 @@ impl Timings {
             }
         }
 
-        data.sort_unstable_by(|a, b| a.day.cmp(&b.day));
+        data.sort_unstable_by_key(|a| a.day);
         Timings { data }
     }
 
@@ -65,6 +67,37 @@ impl Timings {
             .iter()
             .any(|t| t.day == day && t.part_1.is_some() && t.part_2.is_some())
     }
+
+    /// Compare the total time of every day that was timed in both `self` and `baseline`.
+    pub fn compare(&self, baseline: &Self) -> Vec<Comparison> {
+        self.data
+            .iter()
+            .filter_map(|timing| {
+                let baseline = baseline.data.iter().find(|t| t.day == timing.day)?;
+                Some(Comparison {
+                    day: timing.day,
+                    nanos: timing.total_nanos,
+                    baseline_nanos: baseline.total_nanos,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Total time of a day in two benchmark runs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Comparison {
+    pub day: Day,
+    pub nanos: f64,
+    pub baseline_nanos: f64,
+}
+
+impl Comparison {
+    /// Relative change against the baseline in percent, negative if faster.
+    pub fn change_percent(&self) -> Option<f64> {
+        (self.baseline_nanos > 0.0)
+            .then(|| (self.nanos - self.baseline_nanos) / self.baseline_nanos * 100.0)
+    }
 }
 
 /* -------------------------------------------------------------------------- */
@@ -113,6 +146,14 @@ impl From<&Timing> for JsonValue {
 
         map.insert("day".into(), JsonValue::String(value.day.to_string()));
         map.insert("total_nanos".into(), JsonValue::Number(value.total_nanos));
+        map.insert(
+            "threads".into(),
+            match value.threads {
+                #[allow(clippy::cast_precision_loss)]
+                Some(threads) => JsonValue::Number(threads as f64),
+                None => JsonValue::Null,
+            },
+        );
 
         let part_1 = value.part_1.clone().map(JsonValue::String);
         let part_2 = value.part_2.clone().map(JsonValue::String);
@@ -166,11 +207,23 @@ impl TryFrom<&JsonValue> for Timing {
             .and_then(|v| v.get::<f64>().copied())
             .ok_or("Expected timing.total_nanos to be a number.")?;
 
+        // NOTE: timings stored before thread counts were recorded have no `threads` key.
+        let threads = match json.get("threads") {
+            None => None,
+            Some(v) if v.is_null() => None,
+            Some(v) => Some(
+                v.get::<f64>()
+                    .map(|threads| *threads as usize)
+                    .ok_or("Expected timing.threads to be null or a number.")?,
+            ),
+        };
+
         Ok(Timing {
             day,
             part_1: part_1.cloned(),
             part_2: part_2.cloned(),
             total_nanos,
+            threads,
         })
     }
 }
@@ -191,18 +244,21 @@ mod tests {
                     part_1: Some("10ms".into()),
                     part_2: Some("20ms".into()),
                     total_nanos: 3e+10,
+                    threads: None,
                 },
                 Timing {
                     day: day!(2),
                     part_1: Some("30ms".into()),
                     part_2: Some("40ms".into()),
                     total_nanos: 7e+10,
+                    threads: None,
                 },
                 Timing {
                     day: day!(4),
                     part_1: Some("40ms".into()),
                     part_2: None,
                     total_nanos: 4e+10,
+                    threads: None,
                 },
             ],
         }
@@ -221,6 +277,14 @@ mod tests {
             assert_eq!(timing.part_1, Some("1ms".to_string()));
             assert_eq!(timing.part_2, None);
             assert_eq!(timing.total_nanos, 1_000_000_000_f64);
+            assert_eq!(timing.threads, None);
+        }
+
+        #[test]
+        fn handles_thread_counts() {
+            let json = r#"{ "data": [{ "day": "06", "part_1": "1ms", "part_2": "2ms", "total_nanos": 3000000, "threads": 8 }] }"#.to_string();
+            let timings = Timings::try_from(json).unwrap();
+            assert_eq!(timings.data[0].threads, Some(8));
         }
 
         #[test]
@@ -282,10 +346,11 @@ mod tests {
                     part_1: Some("1ms".into()),
                     part_2: Some("2ms".into()),
                     total_nanos: 3_000_000_000_f64,
+                    threads: None,
                 }],
             };
 
-            assert_eq!(timings.is_day_complete(&day!(1)), true);
+            assert_eq!(timings.is_day_complete(day!(1)), true);
         }
 
         #[test]
@@ -296,10 +361,11 @@ mod tests {
                     part_1: Some("1ms".into()),
                     part_2: None,
                     total_nanos: 1_000_000_000_f64,
+                    threads: None,
                 }],
             };
 
-            assert_eq!(timings.is_day_complete(&day!(1)), false);
+            assert_eq!(timings.is_day_complete(day!(1)), false);
         }
 
         #[test]
@@ -310,10 +376,47 @@ mod tests {
                     part_1: None,
                     part_2: None,
                     total_nanos: 0.0,
+                    threads: None,
                 }],
             };
 
-            assert_eq!(timings.is_day_complete(&day!(1)), false);
+            assert_eq!(timings.is_day_complete(day!(1)), false);
+        }
+    }
+
+    mod compare {
+        use crate::{
+            day,
+            template::timings::{Timing, Timings},
+        };
+
+        use super::get_mock_timings;
+
+        #[test]
+        fn compares_common_days() {
+            let timings = get_mock_timings();
+            let baseline = Timings {
+                data: vec![
+                    Timing {
+                        day: day!(2),
+                        part_1: None,
+                        part_2: None,
+                        total_nanos: 1e+11,
+                        threads: None,
+                    },
+                    Timing {
+                        day: day!(3),
+                        part_1: None,
+                        part_2: None,
+                        total_nanos: 1e+10,
+                        threads: None,
+                    },
+                ],
+            };
+            let comparison = timings.compare(&baseline);
+            assert_eq!(comparison.len(), 1);
+            assert_eq!(comparison[0].day, day!(2));
+            assert_eq!(comparison[0].change_percent(), Some(-30.0));
         }
     }
 
@@ -334,6 +437,7 @@ mod tests {
                     part_1: None,
                     part_2: None,
                     total_nanos: 0_f64,
+                    threads: None,
                 }],
             };
             let merged = timings.merge(&other);
@@ -354,6 +458,7 @@ mod tests {
                     part_1: None,
                     part_2: None,
                     total_nanos: 0_f64,
+                    threads: None,
                 }],
             };
             let merged = timings.merge(&other);
@@ -0,0 +1,539 @@
+/// Visualizations for simulation days.
+///
+/// Solutions push [`Frame`]s of a grid with [`push`] or [`push_with`]. Frames are dropped unless the solution is
+/// run with `--viz` (terminal animation, speed set with `--viz-fps <n>`) or `--viz-out <dir>` (numbered images,
+/// format set with `--viz-format png|ppm`). The runner only enables visualizations for the first execution of a
+/// part, never while benching.
+use std::fmt::{self, Display, Write as _};
+use std::io::{stdout, Write as _};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use std::{fs, str::FromStr};
+
+const DEFAULT_FPS: u32 = 10;
+const PIXELS_PER_CELL: usize = 4;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static OUTPUT: Mutex<Option<Output>> = Mutex::new(None);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+    pub const GREY: Color = Color::rgb(128, 128, 128);
+    pub const RED: Color = Color::rgb(220, 50, 47);
+    pub const GREEN: Color = Color::rgb(64, 200, 64);
+    pub const BLUE: Color = Color::rgb(38, 139, 210);
+    pub const YELLOW: Color = Color::rgb(230, 200, 0);
+    pub const CYAN: Color = Color::rgb(42, 161, 152);
+    pub const MAGENTA: Color = Color::rgb(211, 54, 130);
+
+    #[must_use]
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+}
+
+/// A character with optional foreground and background colours.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+}
+
+impl Cell {
+    #[must_use]
+    pub const fn new(ch: char) -> Self {
+        Self {
+            ch,
+            fg: None,
+            bg: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn fg(mut self, color: Color) -> Self {
+        self.fg = Some(color);
+        self
+    }
+
+    #[must_use]
+    pub const fn bg(mut self, color: Color) -> Self {
+        self.bg = Some(color);
+        self
+    }
+
+    /// The colour of the cell when drawn as an image: the background, the foreground or white for any visible character.
+    fn pixel(&self) -> Color {
+        self.bg.or(self.fg).unwrap_or(match self.ch {
+            ' ' | '.' => Color::BLACK,
+            _ => Color::WHITE,
+        })
+    }
+}
+
+impl From<char> for Cell {
+    fn from(ch: char) -> Self {
+        Self::new(ch)
+    }
+}
+
+/// A single image of a visualization. Positions are `(x, y)` with the origin in the top left corner.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+    caption: Option<String>,
+}
+
+impl Frame {
+    /// Create a frame filled with `.`.
+    #[must_use]
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Cell::new('.'); width * height],
+            caption: None,
+        }
+    }
+
+    /// Create a frame from lines of text. Short lines are padded with spaces.
+    #[must_use]
+    pub fn from_text(text: &str) -> Self {
+        let lines: Vec<&str> = text.lines().collect();
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let mut frame = Self::new(width, lines.len());
+        frame.cells.fill(Cell::new(' '));
+        for (y, line) in lines.iter().enumerate() {
+            for (x, ch) in line.chars().enumerate() {
+                frame.set(x, y, ch);
+            }
+        }
+        frame
+    }
+
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    #[must_use]
+    pub fn get(&self, x: usize, y: usize) -> Option<&Cell> {
+        (x < self.width && y < self.height).then(|| &self.cells[y * self.width + x])
+    }
+
+    /// Set a cell. Returns `false` if the position is out of bounds.
+    pub fn set(&mut self, x: usize, y: usize, cell: impl Into<Cell>) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        self.cells[y * self.width + x] = cell.into();
+        true
+    }
+
+    /// Set a cell using signed coordinates. Returns `false` if the position is out of bounds.
+    pub fn set_signed(&mut self, x: i64, y: i64, cell: impl Into<Cell>) -> bool {
+        match (usize::try_from(x), usize::try_from(y)) {
+            (Ok(x), Ok(y)) => self.set(x, y, cell),
+            _ => false,
+        }
+    }
+
+    /// Add a line of text that is shown below the frame in the terminal.
+    #[must_use]
+    pub fn caption(mut self, caption: impl Into<String>) -> Self {
+        self.caption = Some(caption.into());
+        self
+    }
+
+    /// Render the frame with ANSI colour codes.
+    #[must_use]
+    pub fn render_ansi(&self) -> String {
+        let mut out = String::new();
+        for row in self.cells.chunks(self.width.max(1)) {
+            for cell in row {
+                if let Some(c) = cell.fg {
+                    let _ = write!(out, "\x1b[38;2;{};{};{}m", c.r, c.g, c.b);
+                }
+                if let Some(c) = cell.bg {
+                    let _ = write!(out, "\x1b[48;2;{};{};{}m", c.r, c.g, c.b);
+                }
+                out.push(cell.ch);
+                if cell.fg.is_some() || cell.bg.is_some() {
+                    out.push_str("\x1b[0m");
+                }
+            }
+            out.push('\n');
+        }
+        if let Some(caption) = &self.caption {
+            out.push_str(caption);
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Encode the frame as a binary PPM (P6) image with `scale` pixels per cell.
+    #[must_use]
+    pub fn to_ppm(&self, scale: usize) -> Vec<u8> {
+        let (width, height) = (self.width * scale, self.height * scale);
+        let mut out = format!("P6\n{width} {height}\n255\n").into_bytes();
+        for y in 0..height {
+            out.extend(self.pixel_row(y / scale, scale));
+        }
+        out
+    }
+
+    /// Encode the frame as an uncompressed PNG image with `scale` pixels per cell.
+    #[must_use]
+    pub fn to_png(&self, scale: usize) -> Vec<u8> {
+        let (width, height) = (self.width * scale, self.height * scale);
+
+        // every scanline starts with filter type 0 (none).
+        let mut raw = Vec::with_capacity(height * (width * 3 + 1));
+        for y in 0..height {
+            raw.push(0);
+            raw.extend(self.pixel_row(y / scale, scale));
+        }
+
+        let mut ihdr = Vec::with_capacity(13);
+        ihdr.extend(u32::try_from(width).unwrap().to_be_bytes());
+        ihdr.extend(u32::try_from(height).unwrap().to_be_bytes());
+        // 8 bit depth, RGB, deflate, adaptive filtering, no interlace.
+        ihdr.extend([8, 2, 0, 0, 0]);
+
+        let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+        png_chunk(&mut out, b"IHDR", &ihdr);
+        png_chunk(&mut out, b"IDAT", &zlib_stored(&raw));
+        png_chunk(&mut out, b"IEND", &[]);
+        out
+    }
+
+    fn pixel_row(&self, y: usize, scale: usize) -> impl Iterator<Item = u8> + '_ {
+        self.cells[y * self.width..(y + 1) * self.width]
+            .iter()
+            .flat_map(move |cell| {
+                let c = cell.pixel();
+                [c.r, c.g, c.b].repeat(scale)
+            })
+    }
+}
+
+impl Display for Frame {
+    /// Plain text without colours, e.g. for `aoc_debug!`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.cells.chunks(self.width.max(1)) {
+            for cell in row {
+                f.write_char(cell.ch)?;
+            }
+            f.write_char('\n')?;
+        }
+        if let Some(caption) = &self.caption {
+            writeln!(f, "{caption}")?;
+        }
+        Ok(())
+    }
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend(u32::try_from(data.len()).unwrap().to_be_bytes());
+    out.extend(kind);
+    out.extend(data);
+    out.extend(crc32(&[kind.as_slice(), data].concat()).to_be_bytes());
+}
+
+/// Wrap data in a zlib stream of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let len = u16::try_from(block.len()).unwrap();
+        out.push(u8::from(blocks.peek().is_none()));
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffff_u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1_u32, 0_u32);
+    for &byte in data {
+        a = (a + u32::from(byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Ppm,
+}
+
+impl FromStr for ImageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "png" => Ok(ImageFormat::Png),
+            "ppm" => Ok(ImageFormat::Ppm),
+            _ => Err(format!("unknown image format \"{s}\", expected png or ppm")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Target {
+    /// Animate frames in the terminal, waiting `delay` after each frame.
+    Terminal { delay: Duration },
+    /// Write numbered images to `dir`.
+    Images { dir: PathBuf, format: ImageFormat },
+}
+
+/// Where pushed frames go and how they are named.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Options {
+    pub target: Target,
+    /// Prefix of image file names, e.g. `part1`.
+    pub prefix: String,
+}
+
+impl Options {
+    /// Read the `--viz*` flags from command-line arguments. Returns `Ok(None)` if no visualization was requested.
+    pub fn from_args(args: &[String], prefix: &str) -> Result<Option<Self>, String> {
+        let value = |flag: &str| -> Result<Option<&String>, String> {
+            match args.iter().position(|a| a == flag) {
+                Some(i) => args
+                    .get(i + 1)
+                    .map(Some)
+                    .ok_or_else(|| format!("{flag} requires a value")),
+                None => Ok(None),
+            }
+        };
+
+        let target = if let Some(dir) = value("--viz-out")? {
+            let format = value("--viz-format")?.map_or(Ok(ImageFormat::Png), |f| f.parse())?;
+            Target::Images {
+                dir: PathBuf::from(dir),
+                format,
+            }
+        } else if args.iter().any(|a| a == "--viz") || value("--viz-fps")?.is_some() {
+            let fps = value("--viz-fps")?.map_or(Ok(DEFAULT_FPS), |fps| {
+                fps.parse::<u32>()
+                    .ok()
+                    .filter(|fps| *fps > 0)
+                    .ok_or_else(|| format!("invalid --viz-fps \"{fps}\""))
+            })?;
+            Target::Terminal {
+                delay: Duration::from_secs(1) / fps,
+            }
+        } else {
+            return Ok(None);
+        };
+
+        Ok(Some(Options {
+            target,
+            prefix: prefix.to_string(),
+        }))
+    }
+}
+
+struct Output {
+    options: Options,
+    frames: usize,
+}
+
+impl Output {
+    fn write(&mut self, frame: &Frame) -> Result<(), String> {
+        match &self.options.target {
+            Target::Terminal { delay } => {
+                let mut stdout = stdout().lock();
+                if self.frames == 0 {
+                    let _ = write!(stdout, "\x1b[2J");
+                }
+                // move to the top left, draw and clear whatever is left of a larger previous frame.
+                let _ = write!(stdout, "\x1b[H{}\x1b[J", frame.render_ansi());
+                let _ = stdout.flush();
+                thread::sleep(*delay);
+            }
+            Target::Images { dir, format } => {
+                let (bytes, extension) = match format {
+                    ImageFormat::Png => (frame.to_png(PIXELS_PER_CELL), "png"),
+                    ImageFormat::Ppm => (frame.to_ppm(PIXELS_PER_CELL), "ppm"),
+                };
+                let path = dir.join(format!(
+                    "{}-{:05}.{extension}",
+                    self.options.prefix, self.frames
+                ));
+                fs::write(&path, bytes)
+                    .map_err(|e| format!("couldn't write {}: {e}", path.display()))?;
+            }
+        }
+        self.frames += 1;
+        Ok(())
+    }
+}
+
+/// Whether pushed frames are currently shown or written.
+#[inline]
+#[must_use]
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Start sending pushed frames to the given output.
+pub fn start(options: Options) -> Result<(), String> {
+    if let Target::Images { dir, .. } = &options.target {
+        fs::create_dir_all(dir).map_err(|e| format!("couldn't create {}: {e}", dir.display()))?;
+    }
+    *OUTPUT.lock().unwrap() = Some(Output { options, frames: 0 });
+    ENABLED.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Stop the visualization. Returns the number of frames that were pushed.
+pub fn stop() -> usize {
+    ENABLED.store(false, Ordering::Relaxed);
+    OUTPUT
+        .lock()
+        .unwrap()
+        .take()
+        .map_or(0, |output| output.frames)
+}
+
+/// Push a frame to the visualization, if enabled.
+pub fn push(frame: &Frame) {
+    if !enabled() {
+        return;
+    }
+    if let Some(output) = OUTPUT.lock().unwrap().as_mut() {
+        if let Err(e) = output.write(frame) {
+            eprintln!("viz: {e}");
+            ENABLED.store(false, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Push a frame built by `make_frame`. The frame is only built if the visualization is enabled.
+pub fn push_with(make_frame: impl FnOnce() -> Frame) {
+    if enabled() {
+        push(&make_frame());
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use super::{adler32, crc32, Cell, Color, Frame, ImageFormat, Options, Target};
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn parses_options() {
+        assert_eq!(Options::from_args(&args(&["15"]), "part1"), Ok(None));
+        assert_eq!(
+            Options::from_args(&args(&["15", "--viz", "--viz-fps", "4"]), "part1"),
+            Ok(Some(Options {
+                target: Target::Terminal {
+                    delay: Duration::from_millis(250)
+                },
+                prefix: "part1".into()
+            }))
+        );
+        assert_eq!(
+            Options::from_args(&args(&["--viz-out", "out", "--viz-format", "ppm"]), "part2"),
+            Ok(Some(Options {
+                target: Target::Images {
+                    dir: PathBuf::from("out"),
+                    format: ImageFormat::Ppm
+                },
+                prefix: "part2".into()
+            }))
+        );
+        assert!(Options::from_args(&args(&["--viz-fps", "0"]), "part1").is_err());
+        assert!(Options::from_args(&args(&["--viz-out"]), "part1").is_err());
+        assert!(Options::from_args(&args(&["--viz-out", "a", "--viz-format", "gif"]), "").is_err());
+    }
+
+    #[test]
+    fn builds_frames() {
+        let mut frame = Frame::from_text("#.\n.");
+        assert_eq!((frame.width(), frame.height()), (2, 2));
+        assert!(frame.set(1, 1, Cell::new('@').fg(Color::RED)));
+        assert!(!frame.set(2, 0, '#'));
+        assert!(!frame.set_signed(-1, 0, '#'));
+        assert_eq!(frame.get(1, 1).map(|c| c.ch), Some('@'));
+        assert_eq!(frame.caption("step 1").to_string(), "#.\n.@\nstep 1\n");
+    }
+
+    #[test]
+    fn renders_ansi_colours() {
+        let mut frame = Frame::new(2, 1);
+        frame.set(0, 0, Cell::new('#').fg(Color::rgb(1, 2, 3)));
+        assert_eq!(frame.render_ansi(), "\x1b[38;2;1;2;3m#\x1b[0m.\n");
+    }
+
+    #[test]
+    fn encodes_ppm() {
+        let mut frame = Frame::new(2, 1);
+        frame.set(0, 0, '#');
+        let ppm = frame.to_ppm(1);
+        assert!(ppm.starts_with(b"P6\n2 1\n255\n"));
+        assert_eq!(&ppm[ppm.len() - 6..], &[255, 255, 255, 0, 0, 0]);
+        assert_eq!(frame.to_ppm(2).len(), "P6\n4 2\n255\n".len() + 4 * 2 * 3);
+    }
+
+    #[test]
+    fn encodes_png() {
+        let png = Frame::new(3, 2).to_png(1);
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x03\0\0\0\x02"));
+        assert!(png.ends_with(b"\0\0\0\0IEND\xae\x42\x60\x82"));
+    }
+
+    #[test]
+    fn computes_checksums() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+}
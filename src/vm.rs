@@ -0,0 +1,559 @@
+/// A small register machine for puzzles that run assembly-like programs.
+///
+/// A puzzle only defines its instruction set: an enum that implements [`Instruction`], i.e. how to decode an
+/// instruction from [`Token`]s and what it does to the machine's [`State`]. Programs are either parsed with one
+/// instruction per line ([`parse`]) or picked out of noise ([`scan`]), and run on a [`Machine`], which handles
+/// the program counter, breakpoints, tracing and output.
+use std::collections::BTreeSet;
+use std::fmt::{self, Debug, Display};
+use std::ops::{Index, IndexMut};
+
+use crate::aoc_debug;
+use crate::parse::{self, ParseError};
+
+/// The value of a register.
+pub type Word = i64;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VmError {
+    /// A line of the program is not an instruction.
+    Parse(ParseError),
+    /// An instruction failed, e.g. because of an invalid operand.
+    Execute { pc: usize, message: String },
+}
+
+impl Display for VmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VmError::Parse(e) => write!(f, "{e}"),
+            VmError::Execute { pc, message } => write!(f, "instruction {pc}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for VmError {}
+
+impl From<ParseError> for VmError {
+    fn from(e: ParseError) -> Self {
+        VmError::Parse(e)
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// A letter followed by letters, digits, `_` or `'`, e.g. `cpy` or `don't`.
+    Ident,
+    /// Digits with an optional `-`, e.g. `-12`.
+    Int,
+    /// Any other character except whitespace.
+    Punct,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: Kind,
+    pub text: &'a str,
+    /// Byte offset in the tokenized text.
+    pub offset: usize,
+}
+
+impl Token<'_> {
+    #[must_use]
+    pub fn is(&self, text: &str) -> bool {
+        self.text == text
+    }
+
+    /// The value of an [`Kind::Int`] token.
+    #[must_use]
+    pub fn int(&self) -> Option<Word> {
+        match self.kind {
+            Kind::Int => self.text.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Byte offset just after the token.
+    #[must_use]
+    pub fn end(&self) -> usize {
+        self.offset + self.text.len()
+    }
+}
+
+/// Split `text` into tokens. Whitespace separates tokens and is dropped, see [`adjacent`].
+#[must_use]
+pub fn tokenize(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let mut chars = text.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        let digit_follows = chars.peek().is_some_and(|(_, next)| next.is_ascii_digit());
+        let (kind, continues): (Kind, fn(char) -> bool) = match c {
+            c if c.is_whitespace() => continue,
+            c if c.is_alphabetic() => (Kind::Ident, |c| {
+                c.is_alphanumeric() || c == '_' || c == '\''
+            }),
+            c if c.is_ascii_digit() || (c == '-' && digit_follows) => {
+                (Kind::Int, |c| c.is_ascii_digit())
+            }
+            _ => (Kind::Punct, |_| false),
+        };
+        let mut end = offset + c.len_utf8();
+        while let Some(&(i, next)) = chars.peek() {
+            if !continues(next) {
+                break;
+            }
+            end = i + next.len_utf8();
+            chars.next();
+        }
+        tokens.push(Token {
+            kind,
+            text: &text[offset..end],
+            offset,
+        });
+    }
+    tokens
+}
+
+/// Whether the tokens follow each other without whitespace in between.
+#[must_use]
+pub fn adjacent(tokens: &[Token<'_>]) -> bool {
+    tokens.windows(2).all(|w| w[0].end() == w[1].offset)
+}
+
+/// Decode call syntax without whitespace, e.g. `mul(2,4)` or `do()`, at the start of `tokens`. Returns the
+/// name, the arguments and the number of tokens used. Every argument is a single token.
+#[must_use]
+pub fn decode_call<'a>(tokens: &[Token<'a>]) -> Option<(&'a str, Vec<Token<'a>>, usize)> {
+    let [name, open, ..] = tokens else {
+        return None;
+    };
+    if name.kind != Kind::Ident || !open.is("(") {
+        return None;
+    }
+
+    let mut args = vec![];
+    let mut used = 2;
+    if tokens.get(used)?.is(")") {
+        used += 1;
+    } else {
+        loop {
+            let (arg, separator) = (tokens.get(used)?, tokens.get(used + 1)?);
+            if arg.kind == Kind::Punct {
+                return None;
+            }
+            args.push(*arg);
+            used += 2;
+            if separator.is(")") {
+                break;
+            }
+            if !separator.is(",") {
+                return None;
+            }
+        }
+    }
+    adjacent(&tokens[..used]).then_some((name.text, args, used))
+}
+
+/// An instruction set.
+pub trait Instruction: Sized + Debug {
+    /// Decode an instruction at the start of `tokens`. Returns the instruction and the number of tokens used.
+    fn decode(tokens: &[Token<'_>]) -> Option<(Self, usize)>;
+
+    /// Execute the instruction. Errors are reported with the program counter.
+    fn execute(&self, state: &mut State) -> Result<Flow, String>;
+}
+
+/// Where execution continues after an instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Flow {
+    /// The next instruction.
+    Next,
+    /// The instruction at this index.
+    Jump(Word),
+    /// The instruction this far from the current one, e.g. `-1` for the previous one.
+    Offset(Word),
+    /// Stop the program.
+    Halt,
+}
+
+/// Parse a program with one instruction per line. Blank lines are skipped.
+pub fn parse<I: Instruction>(source: &str) -> Result<Vec<I>, ParseError> {
+    parse::lines(source)
+        .filter(|line| !line.text.trim().is_empty())
+        .map(|line| {
+            let tokens = tokenize(line.text);
+            match I::decode(&tokens) {
+                Some((instruction, used)) if used == tokens.len() => Ok(instruction),
+                Some((_, used)) => Err(line.error(
+                    tokens[used].offset + 1,
+                    format!("unexpected `{}`", tokens[used].text),
+                )),
+                None => Err(line.error(1, format!("unknown instruction `{}`", line.text.trim()))),
+            }
+        })
+        .collect()
+}
+
+/// Pick all instructions out of `source` and ignore everything in between.
+#[must_use]
+pub fn scan<I: Instruction>(source: &str) -> Vec<I> {
+    let tokens = tokenize(source);
+    let mut program = vec![];
+    let mut i = 0;
+    while i < tokens.len() {
+        match I::decode(&tokens[i..]) {
+            Some((instruction, used)) => {
+                program.push(instruction);
+                i += used.max(1);
+            }
+            None => i += 1,
+        }
+    }
+    program
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Registers, addressed by index. Puzzles with named registers use [`Registers::index_of`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Registers {
+    values: Vec<Word>,
+}
+
+impl Registers {
+    /// `count` registers set to 0.
+    #[must_use]
+    pub fn new(count: usize) -> Self {
+        Self {
+            values: vec![0; count],
+        }
+    }
+
+    /// Index of a register named by a single lowercase letter: `a` is 0, `b` is 1 and so on.
+    #[must_use]
+    pub fn index_of(name: &str) -> Option<usize> {
+        match name.as_bytes() {
+            [c @ b'a'..=b'z'] => Some(usize::from(c - b'a')),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    #[must_use]
+    pub fn values(&self) -> &[Word] {
+        &self.values
+    }
+}
+
+impl Index<usize> for Registers {
+    type Output = Word;
+
+    fn index(&self, index: usize) -> &Word {
+        &self.values[index]
+    }
+}
+
+impl IndexMut<usize> for Registers {
+    fn index_mut(&mut self, index: usize) -> &mut Word {
+        &mut self.values[index]
+    }
+}
+
+/// Everything that instructions can change.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct State {
+    pub registers: Registers,
+    /// Index of the current instruction.
+    pub pc: usize,
+    /// Values written by output instructions.
+    pub output: Vec<Word>,
+}
+
+/// Why [`Machine::run`] returned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stop {
+    /// The program counter left the program.
+    End,
+    /// An instruction returned [`Flow::Halt`].
+    Halt,
+    /// The instruction at this index has a breakpoint. It runs when the machine is resumed.
+    Breakpoint(usize),
+}
+
+pub struct Machine<I> {
+    program: Vec<I>,
+    state: State,
+    breakpoints: BTreeSet<usize>,
+    trace: bool,
+    steps: usize,
+}
+
+impl<I: Instruction> Machine<I> {
+    /// A machine with `registers` registers set to 0.
+    #[must_use]
+    pub fn new(program: Vec<I>, registers: usize) -> Self {
+        Self {
+            program,
+            state: State {
+                registers: Registers::new(registers),
+                ..State::default()
+            },
+            breakpoints: BTreeSet::new(),
+            trace: false,
+            steps: 0,
+        }
+    }
+
+    #[must_use]
+    pub fn program(&self) -> &[I] {
+        &self.program
+    }
+
+    #[must_use]
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    pub fn state_mut(&mut self) -> &mut State {
+        &mut self.state
+    }
+
+    #[must_use]
+    pub fn registers(&self) -> &Registers {
+        &self.state.registers
+    }
+
+    pub fn registers_mut(&mut self) -> &mut Registers {
+        &mut self.state.registers
+    }
+
+    #[must_use]
+    pub fn output(&self) -> &[Word] {
+        &self.state.output
+    }
+
+    /// Output values joined with commas.
+    #[must_use]
+    pub fn output_string(&self) -> String {
+        let output: Vec<String> = self.state.output.iter().map(Word::to_string).collect();
+        output.join(",")
+    }
+
+    /// Number of instructions executed so far.
+    #[must_use]
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn add_breakpoint(&mut self, pc: usize) {
+        self.breakpoints.insert(pc);
+    }
+
+    pub fn remove_breakpoint(&mut self, pc: usize) {
+        self.breakpoints.remove(&pc);
+    }
+
+    /// Print every executed instruction and the registers after it with `aoc_debug!`.
+    pub fn set_trace(&mut self, trace: bool) {
+        self.trace = trace;
+    }
+
+    /// Execute a single instruction. Returns why the program stopped, if it did.
+    pub fn step(&mut self) -> Result<Option<Stop>, VmError> {
+        let pc = self.state.pc;
+        let Some(instruction) = self.program.get(pc) else {
+            return Ok(Some(Stop::End));
+        };
+        let flow = instruction
+            .execute(&mut self.state)
+            .map_err(|message| VmError::Execute { pc, message })?;
+        self.steps += 1;
+        if self.trace {
+            aoc_debug!(
+                "{pc:>4}: {instruction:?} {:?}",
+                self.state.registers.values()
+            );
+        }
+
+        #[allow(clippy::cast_possible_wrap)]
+        let target = match flow {
+            Flow::Next => pc as Word + 1,
+            Flow::Jump(target) => target,
+            Flow::Offset(offset) => pc as Word + offset,
+            Flow::Halt => return Ok(Some(Stop::Halt)),
+        };
+        match usize::try_from(target) {
+            Ok(target) if target < self.program.len() => {
+                self.state.pc = target;
+                Ok(None)
+            }
+            _ => {
+                self.state.pc = self.program.len();
+                Ok(Some(Stop::End))
+            }
+        }
+    }
+
+    /// Run until the program ends, halts or reaches a breakpoint. A breakpoint at the current instruction is
+    /// ignored, so that `run` resumes after a breakpoint.
+    pub fn run(&mut self) -> Result<Stop, VmError> {
+        loop {
+            if let Some(stop) = self.step()? {
+                return Ok(stop);
+            }
+            if self.breakpoints.contains(&self.state.pc) {
+                return Ok(Stop::Breakpoint(self.state.pc));
+            }
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        decode_call, parse, scan, tokenize, Flow, Instruction, Kind, Machine, Registers, State,
+        Stop, Token, Word,
+    };
+
+    /// The instruction set of 2016's assembunny with an output instruction.
+    #[derive(Debug)]
+    enum Asm {
+        Cpy(Operand, usize),
+        Inc(usize),
+        Dec(usize),
+        Jnz(Operand, Operand),
+        Out(Operand),
+    }
+
+    #[derive(Debug)]
+    enum Operand {
+        Value(Word),
+        Register(usize),
+    }
+
+    impl Operand {
+        fn decode(token: &Token<'_>) -> Option<Operand> {
+            match token.kind {
+                Kind::Int => token.int().map(Operand::Value),
+                _ => Registers::index_of(token.text).map(Operand::Register),
+            }
+        }
+
+        fn get(&self, state: &State) -> Word {
+            match self {
+                Operand::Value(v) => *v,
+                Operand::Register(r) => state.registers[*r],
+            }
+        }
+    }
+
+    impl Instruction for Asm {
+        fn decode(tokens: &[Token<'_>]) -> Option<(Self, usize)> {
+            let register = |t: &Token<'_>| Registers::index_of(t.text);
+            let instruction = match tokens {
+                [op, x, y, ..] if op.is("cpy") => Asm::Cpy(Operand::decode(x)?, register(y)?),
+                [op, x, y, ..] if op.is("jnz") => {
+                    Asm::Jnz(Operand::decode(x)?, Operand::decode(y)?)
+                }
+                [op, x, ..] if op.is("inc") => Asm::Inc(register(x)?),
+                [op, x, ..] if op.is("dec") => Asm::Dec(register(x)?),
+                [op, x, ..] if op.is("out") => Asm::Out(Operand::decode(x)?),
+                _ => return None,
+            };
+            let used = match instruction {
+                Asm::Cpy(..) | Asm::Jnz(..) => 3,
+                _ => 2,
+            };
+            Some((instruction, used))
+        }
+
+        fn execute(&self, state: &mut State) -> Result<Flow, String> {
+            match self {
+                Asm::Cpy(x, r) => state.registers[*r] = x.get(state),
+                Asm::Inc(r) => state.registers[*r] += 1,
+                Asm::Dec(r) => state.registers[*r] -= 1,
+                Asm::Jnz(x, offset) => {
+                    if x.get(state) != 0 {
+                        return Ok(Flow::Offset(offset.get(state)));
+                    }
+                }
+                Asm::Out(x) => state.output.push(x.get(state)),
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    const PROGRAM: &str = "cpy 3 a\ncpy 0 b\n\ninc b\nout b\ndec a\njnz a -3\n";
+
+    #[test]
+    fn tokenizes() {
+        let tokens = tokenize("jnz a -2 don't(4,5)");
+        let texts: Vec<&str> = tokens.iter().map(|t| t.text).collect();
+        assert_eq!(texts, ["jnz", "a", "-2", "don't", "(", "4", ",", "5", ")"]);
+        assert_eq!(tokens[2].int(), Some(-2));
+        assert_eq!(tokens[4].kind, Kind::Punct);
+        assert_eq!(tokenize("a - 2")[1].kind, Kind::Punct);
+    }
+
+    #[test]
+    fn decodes_calls() {
+        let tokens = tokenize("mul(2,4)");
+        let (name, args, used) = decode_call(&tokens).unwrap();
+        assert_eq!((name, args.len(), used), ("mul", 2, 6));
+        assert_eq!(decode_call(&tokenize("do()")).map(|c| c.2), Some(3));
+        assert_eq!(decode_call(&tokenize("mul(2, 4)")), None);
+        assert_eq!(decode_call(&tokenize("mul(2,4]")), None);
+        assert_eq!(decode_call(&tokenize("mul(2,,4)")), None);
+        assert_eq!(decode_call(&tokenize("mul(2,4,)")), None);
+    }
+
+    #[test]
+    fn runs_programs() {
+        let program: Vec<Asm> = parse(PROGRAM).unwrap();
+        assert_eq!(program.len(), 6);
+        let mut machine = Machine::new(program, 2);
+        assert_eq!(machine.run(), Ok(Stop::End));
+        assert_eq!(machine.registers().values(), [0, 3]);
+        assert_eq!(machine.output_string(), "1,2,3");
+        assert_eq!(machine.steps(), 2 + 3 * 4);
+    }
+
+    #[test]
+    fn stops_at_breakpoints() {
+        let mut machine = Machine::new(parse::<Asm>(PROGRAM).unwrap(), 2);
+        machine.add_breakpoint(3);
+        assert_eq!(machine.run(), Ok(Stop::Breakpoint(3)));
+        assert_eq!(machine.output(), [] as [Word; 0]);
+        assert_eq!(machine.run(), Ok(Stop::Breakpoint(3)));
+        assert_eq!(machine.output(), [1]);
+        machine.remove_breakpoint(3);
+        assert_eq!(machine.run(), Ok(Stop::End));
+        assert_eq!(machine.step(), Ok(Some(Stop::End)));
+    }
+
+    #[test]
+    fn reports_parse_errors() {
+        let error = parse::<Asm>("inc a\ninc a b\n").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 7));
+        let error = parse::<Asm>("inc a\nmul a b\n").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
+    }
+
+    #[test]
+    fn scans_noise() {
+        let program: Vec<Asm> = scan("xx inc a ?? dec b inc 7 out 5");
+        assert_eq!(program.len(), 3);
+    }
+}